@@ -34,7 +34,27 @@ fn main() -> anyhow::Result<()> {
         builder = builder.package(Package::Root(PathBuf::from(root_file)))
     }
 
-    Ok(builder.build()?)
+    if matches.is_present("check") {
+        let missing_docs = builder.check()?;
+        for missing_docs in &missing_docs {
+            eprintln!("{}", missing_docs);
+        }
+        if !missing_docs.is_empty() {
+            anyhow::bail!("undocumented exported items: {}", missing_docs.len());
+        }
+        println!("All exported items are documented");
+        Ok(())
+    } else if matches.is_present("dry_run") {
+        let plan = builder.dry_run()?;
+        println!("Documented classes: {}", plan.class_count);
+        println!("Files that would be generated:");
+        for file in plan.files {
+            println!("    {}", file.display());
+        }
+        Ok(())
+    } else {
+        Ok(builder.build()?)
+    }
 }
 
 fn make_app() -> App<'static, 'static> {
@@ -86,6 +106,17 @@ This is useful if you are working within a workspace.",
                     r"Path to the root file of the package for which to build the documentation.",
                 ),
         )
+        .arg(
+            Arg::with_name("dry_run")
+                .long("dry-run")
+                .help("Print the files that would be generated, without writing them"),
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .conflicts_with("dry_run")
+                .help("Exit with an error if some exported items are not documented, without writing any file"),
+        )
         .arg(
             Arg::with_name("verbosity")
                 .long("verbose")
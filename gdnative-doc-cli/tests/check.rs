@@ -0,0 +1,56 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+fn write_root_file(test_name: &str, content: &str) -> PathBuf {
+    let directory = std::env::temp_dir()
+        .join("gdnative-doc-cli-tests")
+        .join(test_name);
+    if directory.exists() {
+        fs::remove_dir_all(&directory).unwrap();
+    }
+    fs::create_dir_all(&directory).unwrap();
+    let root_file = directory.join("lib.rs");
+    fs::write(&root_file, content).unwrap();
+    root_file
+}
+
+fn check(root_file: &Path) -> bool {
+    Command::new(env!("CARGO_BIN_EXE_gdnative-doc-cli"))
+        .arg("--root_file")
+        .arg(root_file)
+        .arg("--check")
+        .output()
+        .unwrap()
+        .status
+        .success()
+}
+
+#[test]
+fn check_undocumented() {
+    let root_file = write_root_file(
+        "check_undocumented",
+        r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Undocumented {}
+"#,
+    );
+    assert!(!check(&root_file));
+}
+
+#[test]
+fn check_documented() {
+    let root_file = write_root_file(
+        "check_documented",
+        r#"
+/// Documented class.
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Documented {}
+"#,
+    );
+    assert!(check(&root_file));
+}
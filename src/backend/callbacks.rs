@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use super::{Generator, Resolver};
-use crate::documentation::{Method, Property};
+use crate::documentation::{Method, Property, PropertyUsage};
 use pulldown_cmark::{CowStr, Event, Tag};
 
 /// Callbacks to encode markdown input in a given format.
@@ -40,6 +40,9 @@ impl dyn Callbacks {
     /// ________
     /// ```
     ///
+    /// `func` is preceded by the gdscript RPC keyword (like `remote`) if the
+    /// method has an RPC mode.
+    ///
     /// With appropriate linking.
     pub fn start_method_default(&mut self, s: &mut String, property: &Resolver, method: &Method) {
         let link = &format!("<a id=\"func-{}\"></a>", method.name);
@@ -50,7 +53,12 @@ impl dyn Callbacks {
                 Event::Html(CowStr::Borrowed(link)),
             ],
         );
-        let mut method_header = String::from("func ");
+        let mut method_header = String::new();
+        if let Some(keyword) = method.rpc_mode.gdscript_keyword() {
+            method_header.push_str(keyword);
+            method_header.push(' ');
+        }
+        method_header.push_str("func ");
         method_header.push_str(&method.name);
         method_header.push('(');
         for (index, (name, typ, _)) in method.parameters.iter().enumerate() {
@@ -80,6 +88,10 @@ impl dyn Callbacks {
     /// ________
     /// ```
     ///
+    /// ` = default` is added after the type if the property has a default value,
+    /// ` (hint: path)` if it has an editor hint, and ` (no editor)` if the
+    /// property is hidden from the editor.
+    ///
     /// With appropriate linking.
     pub fn start_property_default(
         &mut self,
@@ -99,6 +111,15 @@ impl dyn Callbacks {
             ],
         );
         let mut last_events = resolver.encode_type(&property.typ);
+        if let Some(default) = &property.default {
+            last_events.push(Event::Text(format!(" = {}", default).into()));
+        }
+        if let Some(hint) = &property.hint {
+            last_events.push(Event::Text(format!(" (hint: {})", hint).into()));
+        }
+        if property.usage == PropertyUsage::NoEditor {
+            last_events.push(Event::Text(CowStr::Borrowed(" (no editor)")));
+        }
         last_events.push(Event::End(Tag::Heading(3)));
         last_events.push(Event::Rule);
         self.encode(s, last_events);
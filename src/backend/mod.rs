@@ -17,8 +17,13 @@ mod html;
 mod markdown;
 mod resolve;
 
-use crate::documentation::{Documentation, GdnativeClass, Method, Property};
-use pulldown_cmark::{Alignment, CowStr, Event, LinkType, Options as MarkdownOptions, Parser, Tag};
+use crate::{
+    documentation::{Documentation, GdnativeClass, Method, Property},
+    CodeBlockPolicy,
+};
+use pulldown_cmark::{
+    Alignment, CodeBlockKind, CowStr, Event, LinkType, Options as MarkdownOptions, Parser, Tag,
+};
 
 pub(super) use gut::GutCallbacks;
 pub(super) use html::HtmlCallbacks;
@@ -116,6 +121,10 @@ pub struct Generator<'a> {
     ///
     /// See [`ConfigFile::opening_comment`](crate::ConfigFile::opening_comment)
     pub opening_comment: bool,
+    /// Which code blocks are kept in the documentation.
+    ///
+    /// See [`ConfigFile::code_block_policy`](crate::ConfigFile::code_block_policy)
+    pub code_block_policy: CodeBlockPolicy,
 }
 
 impl<'a> Generator<'a> {
@@ -124,12 +133,14 @@ impl<'a> Generator<'a> {
         documentation: &'a Documentation,
         markdown_options: MarkdownOptions,
         opening_comment: bool,
+        code_block_policy: CodeBlockPolicy,
     ) -> Self {
         Self {
             resolver,
             documentation,
             markdown_options,
             opening_comment,
+            code_block_policy,
         }
     }
 
@@ -141,21 +152,22 @@ impl<'a> Generator<'a> {
     ///
     /// # Classes:
     ///
-    /// <list of GDNative classes>
+    /// <list of GDNative classes, sorted by name>
     /// ```
     ///
     /// This then uses [`Callbacks::encode`] to encode this in the target format.
     pub fn generate_root_file(&self, extension: &str, callbacks: &mut dyn Callbacks) -> String {
         let resolver = self.resolver;
         let mut broken_link_callback = broken_link_callback!(resolver);
-        let class_iterator = EventIterator {
-            context: resolver,
-            parser: pulldown_cmark::Parser::new_with_broken_link_callback(
+        let class_iterator = EventIterator::new(
+            resolver,
+            pulldown_cmark::Parser::new_with_broken_link_callback(
                 &self.documentation.root_documentation,
                 self.markdown_options,
                 Some(&mut broken_link_callback),
             ),
-        };
+            self.code_block_policy,
+        );
         let mut events: Vec<_> = class_iterator.into_iter().collect();
         events.extend(vec![
             Event::Start(Tag::Heading(1)),
@@ -163,7 +175,10 @@ impl<'a> Generator<'a> {
             Event::End(Tag::Heading(1)),
             Event::Start(Tag::List(None)),
         ]);
-        for class_name in self.documentation.classes.keys() {
+        // sorted, so that the output is reproducible
+        let mut class_names: Vec<_> = self.documentation.classes.keys().collect();
+        class_names.sort();
+        for class_name in class_names {
             let link = Tag::Link(
                 LinkType::Inline,
                 format!("./{}.{}", class_name, extension).into(),
@@ -259,14 +274,15 @@ impl<'a> Generator<'a> {
 
         // Class description
         let mut broken_link_callback = broken_link_callback!(resolver);
-        let class_documentation = EventIterator {
-            context: resolver,
-            parser: pulldown_cmark::Parser::new_with_broken_link_callback(
+        let class_documentation = EventIterator::new(
+            resolver,
+            pulldown_cmark::Parser::new_with_broken_link_callback(
                 &class.documentation,
                 self.markdown_options,
                 Some(&mut broken_link_callback),
             ),
-        }
+            self.code_block_policy,
+        )
         .into_iter()
         .collect();
         callbacks.encode(&mut class_file, class_documentation);
@@ -298,14 +314,15 @@ impl<'a> Generator<'a> {
             for property in &class.properties {
                 callbacks.start_property(&mut class_file, resolver, property);
                 let mut broken_link_callback = broken_link_callback!(resolver);
-                let property_documentation = EventIterator {
-                    context: resolver,
-                    parser: pulldown_cmark::Parser::new_with_broken_link_callback(
+                let property_documentation = EventIterator::new(
+                    resolver,
+                    pulldown_cmark::Parser::new_with_broken_link_callback(
                         &property.documentation,
                         self.markdown_options,
                         Some(&mut broken_link_callback),
                     ),
-                }
+                    self.code_block_policy,
+                )
                 .into_iter()
                 .collect();
                 callbacks.encode(&mut class_file, property_documentation);
@@ -324,14 +341,15 @@ impl<'a> Generator<'a> {
         for method in &class.methods {
             callbacks.start_method(&mut class_file, resolver, method);
             let mut broken_link_callback = broken_link_callback!(resolver);
-            let method_documentation = EventIterator {
-                context: resolver,
-                parser: pulldown_cmark::Parser::new_with_broken_link_callback(
+            let method_documentation = EventIterator::new(
+                resolver,
+                pulldown_cmark::Parser::new_with_broken_link_callback(
                     &method.documentation,
                     self.markdown_options,
                     Some(&mut broken_link_callback),
                 ),
-            }
+                self.code_block_policy,
+            )
             .into_iter()
             .collect();
             callbacks.encode(&mut class_file, method_documentation);
@@ -449,9 +467,66 @@ impl<'a> Generator<'a> {
 
 /// Iterate over [events](Event), resolving links and changing the resolved
 /// broken links types.
+///
+/// Code blocks are also filtered according to the [`CodeBlockPolicy`].
 struct EventIterator<'resolver, 'parser> {
     context: &'resolver Resolver,
     parser: Parser<'parser>,
+    code_block_policy: CodeBlockPolicy,
+}
+
+impl<'resolver, 'parser> EventIterator<'resolver, 'parser> {
+    fn new(
+        context: &'resolver Resolver,
+        parser: Parser<'parser>,
+        code_block_policy: CodeBlockPolicy,
+    ) -> Self {
+        Self {
+            context,
+            parser,
+            code_block_policy,
+        }
+    }
+
+    /// Returns `true` if a code block of this kind should be removed.
+    fn drop_code_block(&self, kind: &CodeBlockKind) -> bool {
+        let lang = match kind {
+            CodeBlockKind::Indented => "",
+            CodeBlockKind::Fenced(lang) => lang.as_ref(),
+        };
+        match self.code_block_policy {
+            CodeBlockPolicy::Keep => false,
+            CodeBlockPolicy::DropRust => is_rust_code_block(lang),
+            CodeBlockPolicy::GdscriptOnly => lang != "gdscript",
+        }
+    }
+}
+
+/// Attributes of rustdoc code blocks that do not change their language.
+const RUSTDOC_ATTRIBUTES: &[&str] = &[
+    "ignore",
+    "should_panic",
+    "no_run",
+    "compile_fail",
+    "test_harness",
+    "standalone_crate",
+    "edition2015",
+    "edition2018",
+    "edition2021",
+    "edition2024",
+];
+
+/// Returns `true` if rustdoc treats a code block with this info string as Rust.
+///
+/// This is the case if it has a `rust` tag, or if all its tags are rustdoc
+/// attributes (including no tag at all).
+fn is_rust_code_block(lang: &str) -> bool {
+    let tags = || {
+        lang.split(&[',', ' ', '\t'][..])
+            .filter(|tag| !tag.is_empty())
+    };
+    tags().any(|tag| tag == "rust")
+        || tags().all(|tag| RUSTDOC_ATTRIBUTES.contains(&tag) || tag.starts_with("ignore-"))
 }
 
 impl<'resolver, 'parser> Iterator for EventIterator<'resolver, 'parser> {
@@ -459,6 +534,14 @@ impl<'resolver, 'parser> Iterator for EventIterator<'resolver, 'parser> {
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut next_event = self.parser.next()?;
+        // skip the code blocks removed by the policy
+        while let Event::Start(Tag::CodeBlock(kind)) = &next_event {
+            if !self.drop_code_block(kind) {
+                break;
+            }
+            while !matches!(self.parser.next()?, Event::End(Tag::CodeBlock(_))) {}
+            next_event = self.parser.next()?;
+        }
         next_event = match next_event {
             // matches broken reference links that have been restored by the callback
             // and replaces them by shortcut variants
@@ -24,6 +24,11 @@ pub struct Resolver {
     ///
     /// These are defined in the [toml configuration file](crate::ConfigFile).
     pub rename_classes: HashMap<String, String>,
+    /// Link to the documentation of the crate's classes.
+    ///
+    /// This depends on the backend, since the link's extension changes (`.md`,
+    /// `.html`...).
+    pub documented_classes: HashMap<String, String>,
 }
 
 /// Url for the (stable) godot documentation
@@ -65,6 +70,7 @@ impl Default for Resolver {
             rust_to_godot: Self::rust_to_godot(),
             url_overrides: HashMap::new(),
             rename_classes: HashMap::new(),
+            documented_classes: HashMap::new(),
         }
     }
 }
@@ -107,6 +113,20 @@ impl Resolver {
         self.rename_classes = user_config.rename_classes.unwrap_or_default();
     }
 
+    /// Make links to the classes in `documentation` point to their generated file,
+    /// with the given `extension`.
+    pub(crate) fn set_documented_classes(
+        &mut self,
+        documentation: &Documentation,
+        extension: &str,
+    ) {
+        self.documented_classes = documentation
+            .class_names()
+            .into_iter()
+            .map(|name| (name.to_string(), format!("./{}.{}", name, extension)))
+            .collect();
+    }
+
     /// Convert all type names from Rust to Godot.
     ///
     /// This will convert `i32` to `int`, `Int32Array` to `PoolIntArray`...
@@ -147,6 +167,10 @@ impl Resolver {
                 }
             }
             replace(&mut name);
+            class.name = name.clone();
+            for method in &mut class.methods {
+                method.self_type = name.clone();
+            }
             replace(&mut class.inherit);
             renamed_classes.insert(name, class);
         }
@@ -176,11 +200,17 @@ impl Resolver {
         if let Some(path) = self.url_overrides.get(base) {
             Some(path)
         } else {
-            let base = match self.rust_to_godot.get(base) {
+            let base = match self
+                .rename_classes
+                .get(base)
+                .or_else(|| self.rust_to_godot.get(base))
+            {
                 Some(base) => base.as_str(),
                 None => base,
             };
-            if let Some(path) = self.godot_items.get(base) {
+            if let Some(path) = self.documented_classes.get(base) {
+                Some(path)
+            } else if let Some(path) = self.godot_items.get(base) {
                 Some(path)
             } else {
                 None
@@ -231,3 +261,46 @@ impl Resolver {
         events
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::documentation::GdnativeClass;
+    use std::path::PathBuf;
+
+    #[test]
+    fn resolve_documented_classes() {
+        let mut documentation = Documentation {
+            name: "test".to_string(),
+            root_file: PathBuf::from("lib.rs"),
+            root_documentation: String::new(),
+            classes: HashMap::new(),
+        };
+        documentation.classes.insert(
+            "Player".to_string(),
+            GdnativeClass {
+                name: "Player".to_string(),
+                inherit: "Node".to_string(),
+                documentation: String::new(),
+                properties: Vec::new(),
+                methods: Vec::new(),
+                file: PathBuf::from("lib.rs"),
+                allow_missing_docs: false,
+            },
+        );
+        let mut resolver = Resolver::default();
+        resolver
+            .rename_classes
+            .insert("RustPlayer".to_string(), "Player".to_string());
+        resolver.set_documented_classes(&documentation, "md");
+
+        assert_eq!(resolver.resolve("Player"), Some("./Player.md"));
+        assert_eq!(resolver.resolve("RustPlayer"), Some("./Player.md"));
+        assert_eq!(resolver.resolve("crate::Player"), Some("./Player.md"));
+        assert_eq!(resolver.resolve("Enemy"), None);
+        assert_eq!(
+            resolver.resolve("Node"),
+            Some("https://docs.godotengine.org/en/stable/classes/class_node.html")
+        );
+    }
+}
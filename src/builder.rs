@@ -1,9 +1,13 @@
 use crate::{
     backend::{self, BuiltinBackend, Callbacks, Resolver},
-    documentation::Documentation,
-    ConfigFile, Error, Result,
+    documentation::{Documentation, DocumentationOptions, GdnativeClass, MissingDocs},
+    CodeBlockPolicy, ConfigFile, Error, Result,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fs,
+    path::PathBuf,
 };
-use std::{fs, path::PathBuf};
 
 /// Used to specify a crate in [`Builder::package`].
 #[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -14,6 +18,52 @@ pub enum Package {
     Root(PathBuf),
 }
 
+/// Output directory of a backend, with a map from file names to their contents.
+type BackendOutput = (PathBuf, HashMap<String, String>);
+
+/// Files that would be generated by [`Builder::build`].
+///
+/// Obtained via [`Builder::dry_run`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GenerationPlan {
+    /// Number of documented classes.
+    pub class_count: usize,
+    /// Path of each file that would be written, in sorted order.
+    pub files: Vec<PathBuf>,
+}
+
+/// Transformation applied to each class after the documentation is extracted,
+/// and before it is rendered by the backends.
+///
+/// This can be used to rename classes, filter methods, add documentation...
+///
+/// When a transformation renames a class, the class's methods are updated
+/// accordingly, but references to the old name in other classes (e.g. in
+/// parameter types) are not: prefer
+/// [`ConfigFile::rename_classes`](crate::ConfigFile::rename_classes) for that.
+/// If two classes end up with the same name, only the first one (by original
+/// name) is kept, and a warning is emitted.
+///
+/// It is implemented for closures taking a `&mut GdnativeClass`.
+///
+/// See [`Builder::add_class_transform`].
+pub trait ClassTransform {
+    /// Modify `class` in place.
+    fn transform(&self, class: &mut GdnativeClass);
+}
+
+impl<F: Fn(&mut GdnativeClass)> ClassTransform for F {
+    fn transform(&self, class: &mut GdnativeClass) {
+        self(class)
+    }
+}
+
+impl std::fmt::Debug for dyn ClassTransform {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("ClassTransform")
+    }
+}
+
 #[derive(Debug)]
 /// A builder for generating godot documentation in various formats.
 ///
@@ -30,6 +80,10 @@ pub struct Builder {
     user_config: Option<ConfigFile>,
     /// Used to disambiguate which crate to use.
     package: Option<Package>,
+    /// Options for the extraction of the documentation.
+    documentation_options: DocumentationOptions,
+    /// Transformations applied to the classes, in order.
+    class_transforms: Vec<Box<dyn ClassTransform>>,
 }
 
 impl Default for Builder {
@@ -46,6 +100,8 @@ impl Builder {
             backends: Vec::new(),
             user_config: None,
             package: None,
+            documentation_options: DocumentationOptions::default(),
+            class_transforms: Vec::new(),
         }
     }
 
@@ -77,6 +133,72 @@ impl Builder {
         self
     }
 
+    /// Resolve documentation written as `#[doc = include_str!("path")]`.
+    ///
+    /// The included file is read relative to the source file containing the
+    /// attribute. If it cannot be read, a warning is emitted and it is ignored.
+    ///
+    /// This is disabled by default, since it requires reading additional files.
+    ///
+    /// # Example
+    /// ```
+    /// # use gdnative_doc::Builder;
+    /// let builder = Builder::new().resolve_include_str(true);
+    /// ```
+    pub fn resolve_include_str(mut self, resolve: bool) -> Self {
+        self.documentation_options.resolve_include_str = resolve;
+        self
+    }
+
+    /// Only document the classes marked with `#[attribute]`, or declared in a
+    /// module marked with `#[attribute]`.
+    ///
+    /// By default, all classes are documented.
+    ///
+    /// # Example
+    /// ```
+    /// # use gdnative_doc::Builder;
+    /// let builder = Builder::new().include_only_attr("godot_doc".to_string());
+    /// ```
+    pub fn include_only_attr(mut self, attribute: String) -> Self {
+        self.documentation_options.include_only_attr = Some(attribute);
+        self
+    }
+
+    /// Skip the module files bigger than `max_file_bytes`, emitting a warning.
+    ///
+    /// This avoids parsing huge generated files. The root file is always read.
+    ///
+    /// By default, there is no limit.
+    ///
+    /// # Example
+    /// ```
+    /// # use gdnative_doc::Builder;
+    /// let builder = Builder::new().max_file_bytes(1_000_000);
+    /// ```
+    pub fn max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.documentation_options.max_file_bytes = Some(max_file_bytes);
+        self
+    }
+
+    /// Add a transformation to apply to each class before rendering.
+    ///
+    /// Transformations are applied in the order in which they were added, after
+    /// the classes are renamed by the [configuration file](ConfigFile::rename_classes).
+    ///
+    /// # Example
+    /// ```
+    /// # use gdnative_doc::{documentation::GdnativeClass, Builder};
+    /// // Don't document methods starting with '_'
+    /// let builder = Builder::new().add_class_transform(Box::new(|class: &mut GdnativeClass| {
+    ///     class.methods.retain(|method| !method.name.starts_with('_'))
+    /// }));
+    /// ```
+    pub fn add_class_transform(mut self, transform: Box<dyn ClassTransform>) -> Self {
+        self.class_transforms.push(transform);
+        self
+    }
+
     /// Add a new builtin backend to the builder.
     ///
     /// # Example
@@ -113,31 +235,9 @@ impl Builder {
     /// This will generate the documentation for each
     /// [specified backend](Self::add_backend), creating the ouput directories if
     /// needed.
-    #[allow(clippy::or_fun_call)]
-    pub fn build(mut self) -> Result<()> {
-        let (markdown_options, opening_comment) = if let Some(user_config) = self.user_config.take()
-        {
-            let opening_comment = user_config.opening_comment.unwrap_or(true);
-            let markdown_options = user_config
-                .markdown_options()
-                .unwrap_or(pulldown_cmark::Options::empty());
-            self.resolver.apply_user_config(user_config);
-            (markdown_options, opening_comment)
-        } else {
-            (pulldown_cmark::Options::empty(), true)
-        };
-
-        let documentation = self.build_documentation()?;
-        for (mut callbacks, output_dir) in self.backends {
-            let generator = backend::Generator::new(
-                &self.resolver,
-                &documentation,
-                markdown_options,
-                opening_comment,
-            );
-
-            let files = callbacks.generate_files(generator);
-
+    pub fn build(self) -> Result<()> {
+        let (_, outputs) = self.generate()?;
+        for (output_dir, files) in outputs {
             if let Err(err) = fs::create_dir_all(&output_dir) {
                 return Err(Error::Io(output_dir, err));
             }
@@ -152,6 +252,86 @@ impl Builder {
         Ok(())
     }
 
+    /// Compute what [`build`](Self::build) would generate, without writing
+    /// anything.
+    ///
+    /// This is useful to catch misconfigurations (wrong output directory, no
+    /// documented classes...) early.
+    pub fn dry_run(self) -> Result<GenerationPlan> {
+        let (documentation, outputs) = self.generate()?;
+        let mut files: Vec<PathBuf> = outputs
+            .into_iter()
+            .flat_map(|(output_dir, files)| {
+                files
+                    .into_keys()
+                    .map(move |file_name| output_dir.join(file_name))
+            })
+            .collect();
+        files.sort_unstable();
+        Ok(GenerationPlan {
+            class_count: documentation.classes.len(),
+            files,
+        })
+    }
+
+    /// Extract the documentation, and return the exported items without
+    /// documentation.
+    ///
+    /// Nothing is written, and the backends are not run. This is meant to be
+    /// used in CI, to ensure that everything is documented.
+    ///
+    /// This only reports missing documentation: other problems, like invalid
+    /// property names or unregistered classes, are only logged as warnings.
+    ///
+    /// See [`Documentation::missing_docs`].
+    pub fn check(mut self) -> Result<Vec<MissingDocs>> {
+        if let Some(user_config) = self.user_config.take() {
+            self.resolver.apply_user_config(user_config);
+        }
+        let documentation = self.build_documentation()?;
+        Ok(documentation.missing_docs())
+    }
+
+    /// Generate the content of the files for each backend, alongside with their
+    /// output directory.
+    #[allow(clippy::or_fun_call)]
+    fn generate(mut self) -> Result<(Documentation, Vec<BackendOutput>)> {
+        let (markdown_options, opening_comment, code_block_policy) =
+            if let Some(user_config) = self.user_config.take() {
+                let opening_comment = user_config.opening_comment.unwrap_or(true);
+                let code_block_policy = user_config.code_block_policy.unwrap_or_default();
+                let markdown_options = user_config
+                    .markdown_options()
+                    .unwrap_or(pulldown_cmark::Options::empty());
+                self.resolver.apply_user_config(user_config);
+                (markdown_options, opening_comment, code_block_policy)
+            } else {
+                (
+                    pulldown_cmark::Options::empty(),
+                    true,
+                    CodeBlockPolicy::default(),
+                )
+            };
+
+        let documentation = self.build_documentation()?;
+        let mut outputs = Vec::new();
+        for (mut callbacks, output_dir) in self.backends {
+            let mut resolver = self.resolver.clone();
+            resolver.set_documented_classes(&documentation, callbacks.extension());
+            let generator = backend::Generator::new(
+                &resolver,
+                &documentation,
+                markdown_options,
+                opening_comment,
+                code_block_policy,
+            );
+
+            outputs.push((output_dir, callbacks.generate_files(generator)));
+        }
+
+        Ok((documentation, outputs))
+    }
+
     /// Build documentation from a root file.
     ///
     /// The root file is either stored in `self`, or automatically discovered using
@@ -164,8 +344,35 @@ impl Builder {
             None => find_root_file(None)?,
         };
 
-        let mut documentation = Documentation::from_root_file(name, root_file)?;
+        let mut documentation =
+            Documentation::from_root_file(name, root_file, self.documentation_options.clone())?;
         self.resolver.rename_classes(&mut documentation);
+        if !self.class_transforms.is_empty() {
+            // sorted, so that name collisions are resolved reproducibly
+            let mut classes: Vec<_> = std::mem::take(&mut documentation.classes)
+                .into_iter()
+                .collect();
+            classes.sort_by(|(name1, _), (name2, _)| name1.cmp(name2));
+            for (name, mut class) in classes {
+                for transform in &self.class_transforms {
+                    transform.transform(&mut class);
+                }
+                // the transformations may have renamed the class
+                for method in &mut class.methods {
+                    method.self_type = class.name.clone();
+                }
+                match documentation.classes.entry(class.name.clone()) {
+                    Entry::Occupied(_) => log::warn!(
+                        "class '{}' was renamed to '{}', which is already taken: ignoring it",
+                        name,
+                        class.name
+                    ),
+                    Entry::Vacant(entry) => {
+                        entry.insert(class);
+                    }
+                }
+            }
+        }
         Ok(documentation)
     }
 }
@@ -207,3 +414,304 @@ fn find_root_file(package_name: Option<&str>) -> Result<(String, PathBuf)> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `content` as the `lib.rs` of a fresh temporary directory named
+    /// after `test_name`.
+    ///
+    /// Returns the directory and the path of `lib.rs`.
+    fn write_root_file(test_name: &str, content: &str) -> (PathBuf, PathBuf) {
+        let directory = std::env::temp_dir()
+            .join("gdnative-doc-tests")
+            .join("builder")
+            .join(test_name);
+        if directory.exists() {
+            fs::remove_dir_all(&directory).unwrap();
+        }
+        fs::create_dir_all(&directory).unwrap();
+        let root_file = directory.join("lib.rs");
+        fs::write(&root_file, content).unwrap();
+        (directory, root_file)
+    }
+
+    #[test]
+    fn dry_run() {
+        let (directory, root_file) = write_root_file(
+            "dry_run",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Enemy {}
+"#,
+        );
+        let output_dir = directory.join("doc");
+
+        let plan = Builder::new()
+            .package(Package::Root(root_file))
+            .add_backend(BuiltinBackend::Markdown, output_dir.clone())
+            .add_backend(BuiltinBackend::Gut, output_dir.join("gut"))
+            .dry_run()
+            .unwrap();
+
+        assert_eq!(plan.class_count, 2);
+        assert_eq!(
+            plan.files,
+            [
+                output_dir.join("Enemy.md"),
+                output_dir.join("Player.md"),
+                output_dir.join("gut").join("Enemy.gd"),
+                output_dir.join("gut").join("Player.gd"),
+                output_dir.join("index.md"),
+            ]
+        );
+        assert!(!output_dir.exists());
+    }
+
+    #[test]
+    fn class_transform() {
+        let (_, root_file) = write_root_file(
+            "class_transform",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+
+#[methods]
+impl Player {
+    pub fn new(_: &Node) -> Self { todo!() }
+    #[export]
+    pub fn jump(&self, _: &Node) {}
+    #[export]
+    pub fn _private_jump(&self, _: &Node) {}
+}
+"#,
+        );
+
+        let mut builder = Builder::new()
+            .package(Package::Root(root_file))
+            .add_class_transform(Box::new(|class: &mut GdnativeClass| {
+                class
+                    .methods
+                    .retain(|method| !method.name.starts_with("_private"))
+            }))
+            .add_class_transform(Box::new(|class: &mut GdnativeClass| {
+                class.name = format!("Godot{}", class.name)
+            }))
+            .user_config(
+                ConfigFile::load_from_str(r#"rename_classes = { Player = "RenamedPlayer" }"#)
+                    .unwrap(),
+            );
+        builder
+            .resolver
+            .apply_user_config(builder.user_config.take().unwrap());
+        let documentation = builder.build_documentation().unwrap();
+
+        let class = &documentation.classes["GodotRenamedPlayer"];
+        assert_eq!(class.name, "GodotRenamedPlayer");
+        let methods: Vec<_> = class.methods.iter().map(|method| &method.name).collect();
+        assert_eq!(methods, ["new", "jump"]);
+        assert!(class
+            .methods
+            .iter()
+            .all(|method| method.self_type == "GodotRenamedPlayer"));
+    }
+
+    #[test]
+    fn class_transform_collision() {
+        let (_, root_file) = write_root_file(
+            "class_transform_collision",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Alpha {}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Beta {}
+"#,
+        );
+
+        let documentation = Builder::new()
+            .package(Package::Root(root_file))
+            .add_class_transform(Box::new(|class: &mut GdnativeClass| {
+                class.inherit = class.name.clone();
+                class.name = "Same".to_string();
+            }))
+            .build_documentation()
+            .unwrap();
+
+        assert_eq!(documentation.classes.len(), 1);
+        assert_eq!(documentation.classes["Same"].inherit, "Alpha");
+    }
+
+    #[test]
+    fn reproducible_output() {
+        let names = ["Delta", "Alpha", "Echo", "Charlie", "Bravo"];
+        let mut source = String::new();
+        for name in &names {
+            source.push_str(&format!(
+                "#[derive(NativeClass)] #[inherit(Node)] pub struct {} {{}}\n",
+                name
+            ));
+        }
+        let (directory, root_file) = write_root_file("reproducible_output", &source);
+
+        let generate = || {
+            let (_, mut outputs) = Builder::new()
+                .package(Package::Root(root_file.clone()))
+                .add_backend(BuiltinBackend::Markdown, directory.join("doc"))
+                .generate()
+                .unwrap();
+            outputs.remove(0).1.remove("index.md").unwrap()
+        };
+        let index = generate();
+        assert_eq!(index, generate());
+        let positions: Vec<_> = ["Alpha", "Bravo", "Charlie", "Delta", "Echo"]
+            .iter()
+            .map(|name| index.find(&format!("[{}]", name)).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn code_block_policy() {
+        let (directory, root_file) = write_root_file(
+            "code_block_policy",
+            r#"
+//! Start
+//! ```rust,no_run
+//! let rust_tag = 1;
+//! ```
+//! ```
+//! let untagged = 1;
+//! ```
+//! ```ignore
+//! let ignore_tag = 1;
+//! ```
+//! ```no_run
+//! let no_run_tag = 1;
+//! ```
+//! ```rust no_run
+//! let space_separated = 1;
+//! ```
+//! ```gdscript
+//! var gdscript = 1
+//! ```
+//! ```text
+//! text block
+//! ```
+//! End
+"#,
+        );
+
+        let generate = |policy: &str| {
+            let config =
+                ConfigFile::load_from_str(&format!("code_block_policy = \"{}\"", policy)).unwrap();
+            let (_, mut outputs) = Builder::new()
+                .user_config(config)
+                .package(Package::Root(root_file.clone()))
+                .add_backend(BuiltinBackend::Markdown, directory.join("doc"))
+                .generate()
+                .unwrap();
+            let index = outputs.remove(0).1.remove("index.md").unwrap();
+            assert!(index.contains("Start") && index.contains("End"));
+            [
+                "rust_tag",
+                "untagged",
+                "ignore_tag",
+                "no_run_tag",
+                "space_separated",
+                "gdscript",
+                "text block",
+            ]
+            .iter()
+            .filter(|block| index.contains(*block))
+            .copied()
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            generate("keep"),
+            [
+                "rust_tag",
+                "untagged",
+                "ignore_tag",
+                "no_run_tag",
+                "space_separated",
+                "gdscript",
+                "text block"
+            ]
+        );
+        assert_eq!(generate("drop_rust"), ["gdscript", "text block"]);
+        assert_eq!(generate("gdscript_only"), ["gdscript"]);
+    }
+
+    #[test]
+    fn property_hint() {
+        let (directory, root_file) = write_root_file(
+            "property_hint",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    /// Health of the player
+    #[property(default = 10, hint = "Self::health_hint")]
+    health: i32,
+}
+"#,
+        );
+
+        let (_, mut outputs) = Builder::new()
+            .package(Package::Root(root_file))
+            .add_backend(BuiltinBackend::Markdown, directory.join("doc"))
+            .generate()
+            .unwrap();
+        let player = outputs.remove(0).1.remove("Player.md").unwrap();
+        assert!(player.contains(" = 10 (hint: Self::health_hint)"));
+    }
+
+    #[test]
+    fn check() {
+        let (directory, root_file) = write_root_file(
+            "check",
+            r#"
+/// Documented
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Documented {}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct RustUndocumented {}
+"#,
+        );
+        let output_dir = directory.join("doc");
+
+        let missing_docs = Builder::new()
+            .user_config(
+                ConfigFile::load_from_str(
+                    r#"rename_classes = { RustUndocumented = "Undocumented" }"#,
+                )
+                .unwrap(),
+            )
+            .package(Package::Root(root_file.clone()))
+            .add_backend(BuiltinBackend::Markdown, output_dir.clone())
+            .check()
+            .unwrap();
+
+        assert_eq!(
+            missing_docs,
+            [MissingDocs {
+                item: "Undocumented".to_string(),
+                file: root_file
+            }]
+        );
+        assert!(!output_dir.exists());
+    }
+}
@@ -4,6 +4,24 @@ use crate::{Error, Result};
 use serde::Deserialize;
 use std::{collections::HashMap, fs, path::PathBuf};
 
+/// Which code blocks to keep in the generated documentation.
+///
+/// See [`ConfigFile::code_block_policy`].
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeBlockPolicy {
+    /// Keep every code block.
+    #[default]
+    Keep,
+    /// Remove Rust code blocks, as rustdoc sees them: blocks with a `rust` tag
+    /// (like `rust,no_run` or `rust no_run`), blocks whose tags are all rustdoc
+    /// attributes (like `ignore` or `no_run`), untagged fenced blocks and
+    /// indented blocks.
+    DropRust,
+    /// Only keep code blocks tagged `gdscript`.
+    GdscriptOnly,
+}
+
 /// Structure that holds user configuration settings.
 ///
 /// Should be obtained via a `toml` configuration file.
@@ -60,6 +78,14 @@ pub struct ConfigFile {
     /// # Default
     /// `true`
     pub opening_comment: Option<bool>,
+    /// Which code blocks to keep in the generated documentation.
+    ///
+    /// Rust examples are of little use to Godot users, who would rather see
+    /// `gdscript` examples.
+    ///
+    /// # Default
+    /// [`CodeBlockPolicy::Keep`]
+    pub code_block_policy: Option<CodeBlockPolicy>,
 }
 
 impl ConfigFile {
@@ -1,11 +1,18 @@
 use super::{
-    attributes_contains, get_docs, get_type_name, read_file_at, Documentation, GdnativeClass, Type,
+    allows_missing_docs, attributes_contains, get_docs, get_inherited_class, get_path_attribute,
+    get_type_name, normalize_path, read_file_at, Documentation, DocumentationOptions,
+    GdnativeClass, Type,
 };
 use crate::Error;
-use std::{mem, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    path::PathBuf,
+};
 use syn::{
+    ext::IdentExt,
     visit::{self, Visit},
-    ItemImpl, ItemMod, ItemStruct,
+    ExprMethodCall, ItemImpl, ItemMod, ItemStruct, ItemType,
 };
 
 /// Structure that builds the [`Documentation`] by visiting source files.
@@ -21,7 +28,48 @@ pub(super) struct DocumentationBuilder {
     /// It is also `true` for the root file.
     pub(super) current_file: (PathBuf, bool),
     /// Path of the current module in `current_file`.
+    ///
+    /// For inline modules with a `#[path = "..."]` attribute, the path is used
+    /// instead of the module name.
     pub(super) current_module: Vec<String>,
+    /// Canonical paths of the files that are being explored, from the root file
+    /// to `current_file`.
+    ///
+    /// Used to detect modules that include themselves (via `#[path]`).
+    pub(super) file_stack: Vec<PathBuf>,
+    /// Canonical paths of all the files explored so far.
+    ///
+    /// Used to only explore each file once, even if multiple modules point to it
+    /// (via `#[path]`).
+    pub(super) explored_files: HashSet<PathBuf>,
+    /// `true` if the current module is marked with
+    /// [`DocumentationOptions::include_only_attr`], or is inside such a module.
+    pub(super) in_included_module: bool,
+    /// `true` if the `missing_docs` lint is allowed in the current module.
+    pub(super) allow_missing_docs: bool,
+    /// Classes that are not marked with [`DocumentationOptions::include_only_attr`].
+    ///
+    /// They will be removed at the end of the exploration.
+    pub(super) excluded_classes: HashSet<String>,
+    /// Classes registered via `add_class::<T>()` or `add_tool_class::<T>()`.
+    pub(super) registered_classes: HashSet<String>,
+    /// File of the first definition of each GDNative class encountered.
+    ///
+    /// Used to detect multiple definitions of the same class (e.g. under
+    /// different `#[cfg(...)]`).
+    pub(super) defined_classes: HashMap<String, PathBuf>,
+    /// Type aliases (`type Alias = Type;`) encountered, by name.
+    ///
+    /// Aliases declared inside blocks (e.g. function bodies) are ignored.
+    pub(super) type_aliases: HashMap<String, Type>,
+    /// Names of type aliases declared several times with different targets.
+    ///
+    /// They will not be resolved.
+    pub(super) conflicting_type_aliases: HashSet<String>,
+    /// Number of blocks (e.g. function bodies) we are currently in.
+    pub(super) block_depth: usize,
+    /// Options for the extraction of the documentation.
+    pub(super) options: DocumentationOptions,
     /// Error encountered.
     ///
     /// If it is some, the exploration will stop prematuraly and return it.
@@ -29,9 +77,56 @@ pub(super) struct DocumentationBuilder {
 }
 
 impl DocumentationBuilder {
-    /// Given the current context and a module name, returns the 2 possible files
-    /// corresponding to the module (aka `module/mod.rs` and `module.rs`).
-    fn get_module_path(&self, module: &str) -> (PathBuf, PathBuf) {
+    /// Create a new builder, starting the exploration at `root_file`.
+    pub(super) fn new(name: String, root_file: PathBuf, options: DocumentationOptions) -> Self {
+        let canonical_root_file = root_file
+            .canonicalize()
+            .unwrap_or_else(|_| root_file.clone());
+        Self {
+            documentation: Documentation {
+                name,
+                root_file: root_file.clone(),
+                root_documentation: String::new(),
+                classes: HashMap::new(),
+            },
+            file_stack: vec![canonical_root_file.clone()],
+            explored_files: std::iter::once(canonical_root_file).collect(),
+            current_file: (root_file, true),
+            current_module: Vec::new(),
+            in_included_module: false,
+            allow_missing_docs: false,
+            excluded_classes: HashSet::new(),
+            registered_classes: HashSet::new(),
+            defined_classes: HashMap::new(),
+            type_aliases: HashMap::new(),
+            conflicting_type_aliases: HashSet::new(),
+            block_depth: 0,
+            options,
+            error: None,
+        }
+    }
+
+    /// Returns `true` if `attrs` contains the attribute specified in
+    /// [`DocumentationOptions::include_only_attr`].
+    pub(super) fn has_include_only_attr(&self, attrs: &[syn::Attribute]) -> bool {
+        match &self.options.include_only_attr {
+            Some(include_only_attr) => attrs
+                .iter()
+                .any(|attr| attr.path.is_ident(include_only_attr)),
+            None => false,
+        }
+    }
+
+    /// Returns the directory in which the submodules of the current module are
+    /// searched.
+    ///
+    /// - For the root file and `module/mod.rs` files, this is their parent
+    ///   directory.
+    /// - For other files (`module.rs`), this is the path of the file without its
+    ///   extension (`module/`).
+    ///
+    /// The inline modules we are currently in are then added to this directory.
+    pub(super) fn module_directory(&self) -> PathBuf {
         let mut path = self.current_file.0.clone();
         if self.current_file.1 {
             path.pop();
@@ -41,6 +136,30 @@ impl DocumentationBuilder {
         for module in &self.current_module {
             path.push(module);
         }
+        path
+    }
+
+    /// Returns the directory relative to which `#[path = "..."]` attributes are
+    /// resolved.
+    ///
+    /// This is the directory of `current_file`, or [`module_directory`] if we are
+    /// inside an inline module.
+    ///
+    /// [`module_directory`]: Self::module_directory
+    fn path_attribute_directory(&self) -> PathBuf {
+        if self.current_module.is_empty() {
+            let mut path = self.current_file.0.clone();
+            path.pop();
+            path
+        } else {
+            self.module_directory()
+        }
+    }
+
+    /// Given the current context and a module name, returns the 2 possible files
+    /// corresponding to the module (aka `module/mod.rs` and `module.rs`).
+    fn get_module_path(&self, module: &str) -> (PathBuf, PathBuf) {
+        let mut path = self.module_directory();
         path.push(module);
         (path.join("mod.rs"), {
             path.set_extension("rs");
@@ -48,6 +167,65 @@ impl DocumentationBuilder {
         })
     }
 
+    /// Warn about the documented classes that are not registered, and the
+    /// registered classes that are not documented.
+    ///
+    /// Nothing is checked if no registration was found (the classes may be
+    /// registered in another crate).
+    pub(super) fn check_registered_classes(&self) {
+        if self.registered_classes.is_empty() {
+            return;
+        }
+        let mut not_registered: Vec<_> = self
+            .documentation
+            .classes
+            .keys()
+            .filter(|class| !self.registered_classes.contains(*class))
+            .collect();
+        not_registered.sort();
+        for class in not_registered {
+            log::warn!("GDNative class '{}' is never registered", class);
+        }
+        let mut not_documented: Vec<_> = self
+            .registered_classes
+            .iter()
+            .filter(|class| {
+                !self.documentation.classes.contains_key(*class)
+                    && !self.excluded_classes.contains(*class)
+            })
+            .collect();
+        not_documented.sort();
+        for class in not_documented {
+            log::warn!(
+                "'{}' is registered, but no corresponding GDNative class was found",
+                class
+            );
+        }
+    }
+
+    /// Replace the type aliases in the types of methods and properties, by the
+    /// types they stand for.
+    pub(super) fn resolve_type_aliases(&mut self) {
+        for name in &self.conflicting_type_aliases {
+            self.type_aliases.remove(name);
+        }
+        if self.type_aliases.is_empty() {
+            return;
+        }
+        let type_aliases = &self.type_aliases;
+        for class in self.documentation.classes.values_mut() {
+            for method in &mut class.methods {
+                for (_, typ, _) in &mut method.parameters {
+                    resolve_type_alias(type_aliases, typ);
+                }
+                resolve_type_alias(type_aliases, &mut method.return_type);
+            }
+            for property in &mut class.properties {
+                resolve_type_alias(type_aliases, &mut property.typ);
+            }
+        }
+    }
+
     /// Inner function for Visit::visit_item_impl
     ///
     /// Used for the early return
@@ -73,16 +251,61 @@ impl DocumentationBuilder {
                     properties: Vec::new(),
                     methods: Vec::new(),
                     file: PathBuf::new(),
+                    allow_missing_docs: false,
                 });
+            let allow_missing_docs =
+                allows_missing_docs(&impl_block.attrs, self.allow_missing_docs);
+            for item in &impl_block.items {
+                if let syn::ImplItem::Method(method) = item {
+                    class.add_method(
+                        method,
+                        self.current_file.0.clone(),
+                        allow_missing_docs,
+                        &self.options,
+                    );
+                }
+            }
+        } else {
             for item in &impl_block.items {
                 if let syn::ImplItem::Method(method) = item {
-                    class.add_method(method, self.current_file.0.clone());
+                    if method.attrs.iter().any(|attr| attr.path.is_ident("export")) {
+                        log::warn!(
+                            "method '{}' is marked with #[export] outside of a #[methods] impl block: it will not be registered",
+                            method.sig.ident
+                        );
+                    }
                 }
             }
         }
     }
 }
 
+/// Replace `typ` by the type it is an alias of, following chains of aliases.
+fn resolve_type_alias(type_aliases: &HashMap<String, Type>, typ: &mut Type) {
+    let mut visited = HashSet::new();
+    loop {
+        let (name, is_option) = match typ {
+            Type::Named(name) => (name, false),
+            Type::Option(name) => (name, true),
+            Type::Unit => return,
+        };
+        let target = match type_aliases.get(name.as_str()) {
+            Some(target) => target,
+            None => return,
+        };
+        if !visited.insert(name.clone()) {
+            log::warn!("cyclic type alias: '{}'", name);
+            return;
+        }
+        *typ = match (is_option, target) {
+            (false, target) => target.clone(),
+            (true, Type::Named(target)) => Type::Option(target.clone()),
+            // `Option<Option<T>>` and `Option<()>` cannot be represented
+            (true, _) => return,
+        };
+    }
+}
+
 impl<'ast> Visit<'ast> for DocumentationBuilder {
     fn visit_item_mod(&mut self, module: &'ast ItemMod) {
         if self.error.is_some() {
@@ -90,17 +313,66 @@ impl<'ast> Visit<'ast> for DocumentationBuilder {
         }
 
         let file_module: ItemMod;
+        let old_in_included_module = self.in_included_module;
+        let old_allow_missing_docs = self.allow_missing_docs;
+        self.in_included_module |= self.has_include_only_attr(&module.attrs);
+        self.allow_missing_docs = allows_missing_docs(&module.attrs, self.allow_missing_docs);
 
         let (module, old_data) = match &module.content {
-            Some(_) => (module, None),
+            Some(_) => {
+                // `#[path]` on an inline module changes the directory of its submodules
+                self.current_module.push(
+                    get_path_attribute(&module.attrs)
+                        .unwrap_or_else(|| module.ident.unraw().to_string()),
+                );
+                (module, None)
+            }
             None => {
-                let module_name = module.ident.to_string();
-                let (mod_rs, file_rs) = self.get_module_path(&module_name);
-                let (path, mod_rs) = if mod_rs.exists() {
-                    (mod_rs, true)
+                let (path, mod_rs) = if let Some(path) = get_path_attribute(&module.attrs) {
+                    // files included via `#[path]` own their directory, like `mod.rs`
+                    (
+                        normalize_path(&self.path_attribute_directory().join(path)),
+                        true,
+                    )
                 } else {
-                    (file_rs, false)
+                    // `mod r#type;` is in `type.rs`
+                    let module_name = module.ident.unraw().to_string();
+                    let (mod_rs, file_rs) = self.get_module_path(&module_name);
+                    if mod_rs.exists() {
+                        (mod_rs, true)
+                    } else {
+                        (file_rs, false)
+                    }
                 };
+                if let Some(max_file_bytes) = self.options.max_file_bytes {
+                    if let Ok(metadata) = path.metadata() {
+                        if metadata.len() > max_file_bytes {
+                            log::warn!(
+                                "skipping {}: its size ({} bytes) exceeds the limit of {} bytes",
+                                path.display(),
+                                metadata.len(),
+                                max_file_bytes
+                            );
+                            self.in_included_module = old_in_included_module;
+                            self.allow_missing_docs = old_allow_missing_docs;
+                            return;
+                        }
+                    }
+                }
+                let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                if self.file_stack.contains(&canonical_path) {
+                    self.error = Some(Error::CyclicModule(path));
+                    return;
+                }
+                if !self.explored_files.insert(canonical_path.clone()) {
+                    log::warn!(
+                        "{} is included by multiple modules: it will only be documented once",
+                        path.display()
+                    );
+                    self.in_included_module = old_in_included_module;
+                    self.allow_missing_docs = old_allow_missing_docs;
+                    return;
+                }
                 let file = match read_file_at(&path) {
                     Ok(file) => file,
                     Err(err) => {
@@ -121,6 +393,10 @@ impl<'ast> Visit<'ast> for DocumentationBuilder {
                     mem::take(&mut self.current_module),
                 );
                 self.current_file = (path, mod_rs);
+                self.file_stack.push(canonical_path);
+                self.in_included_module |= self.has_include_only_attr(&file_module.attrs);
+                self.allow_missing_docs =
+                    allows_missing_docs(&file_module.attrs, self.allow_missing_docs);
                 (&file_module, Some(old_data))
             }
         };
@@ -129,7 +405,12 @@ impl<'ast> Visit<'ast> for DocumentationBuilder {
         if let Some((old_file, old_module)) = old_data {
             self.current_file = old_file;
             self.current_module = old_module;
+            self.file_stack.pop();
+        } else {
+            self.current_module.pop();
         }
+        self.in_included_module = old_in_included_module;
+        self.allow_missing_docs = old_allow_missing_docs;
     }
 
     fn visit_item_struct(&mut self, strukt: &'ast ItemStruct) {
@@ -139,15 +420,12 @@ impl<'ast> Visit<'ast> for DocumentationBuilder {
         let mut implement_native_class = false;
         let mut inherit = None;
         for attr in &strukt.attrs {
-            if let Ok(syn::Meta::List(syn::MetaList { path, nested, .. })) = attr.parse_meta() {
-                if path.is_ident("inherit") && nested.len() == 1 {
-                    if let Some(syn::NestedMeta::Meta(syn::Meta::Path(path))) = nested.first() {
-                        // TODO: support path of the form "gdnative::Class"
-                        if let Some(class) = path.get_ident() {
-                            inherit = Some(class.to_string())
-                        }
-                    }
-                } else if path.is_ident("derive") && nested.len() == 1 {
+            if attr.path.is_ident("inherit") {
+                inherit = Some(attr);
+            } else if let Ok(syn::Meta::List(syn::MetaList { path, nested, .. })) =
+                attr.parse_meta()
+            {
+                if path.is_ident("derive") && nested.len() == 1 {
                     if let Some(syn::NestedMeta::Meta(syn::Meta::Path(path))) = nested.first() {
                         if path.is_ident("NativeClass") {
                             implement_native_class = true;
@@ -161,33 +439,101 @@ impl<'ast> Visit<'ast> for DocumentationBuilder {
             return;
         }
 
-        if let Some(inherit) = inherit {
-            let self_type = strukt.ident.to_string();
-            log::trace!(
-                "found GDNative class '{}' that inherits '{}'",
-                self_type,
-                inherit
+        let self_type = strukt.ident.to_string();
+        let inherit = match inherit.map(|attr| (attr, get_inherited_class(attr))) {
+            Some((_, Some(inherit))) => inherit,
+            Some((attr, None)) => {
+                self.error = Some(Error::InvalidAttribute(
+                    self.current_file.0.clone(),
+                    format!(
+                        "expected '#[inherit(Class)]' on '{}', found '#[inherit{}]'",
+                        self_type, attr.tokens
+                    ),
+                ));
+                return;
+            }
+            None => {
+                log::warn!(
+                    "GDNative class '{}' has no '#[inherit(...)]' attribute, assuming 'Reference'",
+                    self_type
+                );
+                String::from("Reference")
+            }
+        };
+        if self.options.include_only_attr.is_some()
+            && !self.in_included_module
+            && !self.has_include_only_attr(&strukt.attrs)
+        {
+            log::trace!("excluding GDNative class '{}'", self_type);
+            self.excluded_classes.insert(self_type);
+            return;
+        }
+        log::trace!(
+            "found GDNative class '{}' that inherits '{}'",
+            self_type,
+            inherit
+        );
+        if !strukt.generics.params.is_empty() {
+            log::warn!(
+                "GDNative class '{}' is generic: generic classes cannot be registered in Godot",
+                self_type
             );
-            // FIXME: warn or error if we already visited a struct with the same name
-            // But be careful ! We *could* have encountered the name in an `impl` block, in which case no warning is warranted.
-            let class = self
-                .documentation
-                .classes
-                .entry(self_type.clone())
-                .or_insert(GdnativeClass {
-                    name: self_type,
-                    inherit: String::new(),
-                    documentation: String::new(),
-                    properties: Vec::new(),
-                    methods: Vec::new(),
-                    file: self.current_file.0.clone(),
-                });
-            if let syn::Fields::Named(fields) = &strukt.fields {
-                class.get_properties(fields)
+        }
+        let allow_missing_docs = allows_missing_docs(&strukt.attrs, self.allow_missing_docs);
+        let mut definition = GdnativeClass {
+            name: self_type.clone(),
+            inherit,
+            documentation: get_docs(&strukt.attrs, &self.current_file.0, &self.options),
+            properties: Vec::new(),
+            methods: Vec::new(),
+            file: self.current_file.0.clone(),
+            allow_missing_docs,
+        };
+        if let syn::Fields::Named(fields) = &strukt.fields {
+            definition.get_properties(
+                fields,
+                &self.current_file.0,
+                allow_missing_docs,
+                &self.options,
+            )
+        }
+
+        // The class may already exist if we encountered one of its `impl` blocks.
+        let class = self
+            .documentation
+            .classes
+            .entry(self_type.clone())
+            .or_insert(GdnativeClass {
+                name: self_type.clone(),
+                inherit: String::new(),
+                documentation: String::new(),
+                properties: Vec::new(),
+                methods: Vec::new(),
+                file: self.current_file.0.clone(),
+                allow_missing_docs: false,
+            });
+        // Multiple definitions (e.g. under different `#[cfg(...)]`): keep the first one.
+        if let Some(first_file) = self.defined_classes.get(&self_type) {
+            if class.inherit != definition.inherit
+                || class.documentation != definition.documentation
+                || class.properties != definition.properties
+                || class.allow_missing_docs != definition.allow_missing_docs
+            {
+                log::warn!(
+                    "GDNative class '{}' has conflicting definitions in '{}' and '{}': keeping the first one",
+                    self_type,
+                    first_file.display(),
+                    self.current_file.0.display()
+                );
             }
-            class.inherit = inherit;
-            class.documentation = get_docs(&strukt.attrs);
+            return;
         }
+        self.defined_classes
+            .insert(self_type, self.current_file.0.clone());
+        class.inherit = definition.inherit;
+        class.allow_missing_docs = definition.allow_missing_docs;
+        class.documentation = definition.documentation;
+        class.properties = definition.properties;
     }
 
     fn visit_item_impl(&mut self, impl_block: &'ast ItemImpl) {
@@ -198,4 +544,53 @@ impl<'ast> Visit<'ast> for DocumentationBuilder {
 
         visit::visit_item_impl(self, impl_block)
     }
+
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if self.error.is_some() {
+            return;
+        }
+        if call.method == "add_class" || call.method == "add_tool_class" {
+            if let Some(turbofish) = &call.turbofish {
+                if let Some(syn::GenericMethodArgument::Type(ty)) = turbofish.args.first() {
+                    if let Some(Type::Named(class)) = get_type_name(ty.clone()) {
+                        log::trace!("found registration of '{}'", class);
+                        self.registered_classes.insert(class);
+                    }
+                }
+            }
+        }
+
+        visit::visit_expr_method_call(self, call)
+    }
+
+    fn visit_item_type(&mut self, item_type: &'ast ItemType) {
+        if self.error.is_some() {
+            return;
+        }
+        if item_type.generics.params.is_empty() && self.block_depth == 0 {
+            if let Some(typ) = get_type_name(*item_type.ty.clone()) {
+                log::trace!("found type alias '{}' = {:?}", item_type.ident, typ);
+                let name = item_type.ident.to_string();
+                match self.type_aliases.get(&name) {
+                    Some(previous) if *previous != typ => {
+                        if self.conflicting_type_aliases.insert(name.clone()) {
+                            log::warn!(
+                                "type alias '{}' has conflicting definitions: it will not be resolved",
+                                name
+                            );
+                        }
+                    }
+                    _ => {
+                        self.type_aliases.insert(name, typ);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.block_depth += 1;
+        visit::visit_block(self, block);
+        self.block_depth -= 1;
+    }
 }
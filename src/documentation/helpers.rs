@@ -1,14 +1,79 @@
-use super::Type;
+use super::{DocumentationOptions, RpcMode, Type};
 use crate::{Error, Result};
+use std::path::Path;
 
 /// Read and parse the file at the given `path` with `syn`, reporting any error.
 pub(super) fn read_file_at(path: &std::path::Path) -> Result<syn::File> {
     match std::fs::read_to_string(path) {
         Ok(content) => Ok(syn::parse_file(&content)?),
+        Err(err) if err.kind() == std::io::ErrorKind::InvalidData => {
+            Err(Error::Encoding(path.to_path_buf()))
+        }
         Err(err) => Err(Error::Io(path.to_path_buf(), err)),
     }
 }
 
+/// If `attrs` contains `#[path = "..."]`, returns the specified path.
+pub(super) fn get_path_attribute(attrs: &[syn::Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        if !attr.path.is_ident("path") {
+            return None;
+        }
+        match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(path),
+                ..
+            })) => Some(path.value()),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the class in `#[inherit(Class)]`, or `None` if the attribute is
+/// malformed.
+///
+/// Paths like `#[inherit(gdnative::api::Node)]` are accepted, the class being
+/// their last segment.
+pub(super) fn get_inherited_class(attr: &syn::Attribute) -> Option<String> {
+    match attr.parse_meta() {
+        Ok(syn::Meta::List(syn::MetaList { nested, .. })) if nested.len() == 1 => {
+            match nested.first() {
+                Some(syn::NestedMeta::Meta(syn::Meta::Path(path))) => path
+                    .segments
+                    .last()
+                    .map(|segment| segment.ident.to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Remove the `.` and `..` components of `path`, without accessing the file
+/// system.
+///
+/// `..` components that cannot be removed (at the start of a relative path) are
+/// kept.
+pub(super) fn normalize_path(path: &Path) -> std::path::PathBuf {
+    use std::path::Component;
+
+    let mut normalized = std::path::PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                _ => normalized.push(component),
+            },
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
 /// Returns whether or not `attr` contains `#[attribute]`.
 pub(super) fn attributes_contains(attrs: &[syn::Attribute], attribute: &str) -> bool {
     attrs
@@ -16,7 +81,131 @@ pub(super) fn attributes_contains(attrs: &[syn::Attribute], attribute: &str) ->
         .any(|attr| attr.path.is_ident(attribute) && attr.tokens.is_empty())
 }
 
+/// Returns whether the `missing_docs` lint is allowed for an item with the given
+/// `attrs`, knowing whether it is allowed for the enclosing item.
+///
+/// `#[allow(missing_docs)]` allows it, while `#[warn(missing_docs)]`,
+/// `#[deny(missing_docs)]` and `#[forbid(missing_docs)]` disallow it.
+pub(super) fn allows_missing_docs(attrs: &[syn::Attribute], allowed_in_parent: bool) -> bool {
+    let mut allowed = allowed_in_parent;
+    for attr in attrs {
+        let allow = if attr.path.is_ident("allow") {
+            true
+        } else if ["warn", "deny", "forbid"]
+            .iter()
+            .any(|level| attr.path.is_ident(level))
+        {
+            false
+        } else {
+            continue;
+        };
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            let contains_missing_docs = list.nested.iter().any(|lint| {
+                matches!(lint, syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("missing_docs"))
+            });
+            if contains_missing_docs {
+                allowed = allow;
+            }
+        }
+    }
+    allowed
+}
+
+/// If `attrs` contains `#[export]` or `#[export(...)]`, returns the RPC mode of the
+/// method.
+pub(super) fn get_export_rpc_mode(attrs: &[syn::Attribute]) -> Option<RpcMode> {
+    let attr = attrs.iter().find(|attr| attr.path.is_ident("export"))?;
+    if attr.tokens.is_empty() {
+        return Some(RpcMode::Disabled);
+    }
+    let mut rpc_mode = RpcMode::Disabled;
+    let arguments = match attr.parse_meta() {
+        Ok(syn::Meta::List(list)) => list.nested.into_iter().collect(),
+        Ok(syn::Meta::NameValue(name_value)) => vec![syn::NestedMeta::Meta(name_value.into())],
+        _ => Vec::new(),
+    };
+    for argument in arguments {
+        if let syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+            path,
+            lit: syn::Lit::Str(mode),
+            ..
+        })) = argument
+        {
+            if path.is_ident("rpc") {
+                match RpcMode::parse(&mode.value()) {
+                    Some(mode) => rpc_mode = mode,
+                    None => log::warn!("unknown rpc mode: {}", mode.value()),
+                }
+            }
+        }
+    }
+    Some(rpc_mode)
+}
+
+/// Returns `true` if `name` is a valid identifier in Godot (letters, digits and
+/// `_`, not starting with a digit).
+pub(super) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+/// Returns `lit` as written in the source, or `None` for byte (string) literals.
+pub(super) fn get_literal(lit: &syn::Lit) -> Option<String> {
+    match lit {
+        syn::Lit::Str(string) => Some(format!("{:?}", string.value())),
+        syn::Lit::Char(character) => Some(format!("{:?}", character.value())),
+        syn::Lit::Int(int) => Some(int.base10_digits().to_string()),
+        syn::Lit::Float(float) => Some(float.base10_digits().to_string()),
+        syn::Lit::Bool(boolean) => Some(boolean.value.to_string()),
+        _ => None,
+    }
+}
+
+/// If `attrs` contains `#[property]` or `#[property(...)]`, returns the arguments
+/// of the attribute.
+pub(super) fn get_property_arguments(attrs: &[syn::Attribute]) -> Option<Vec<syn::NestedMeta>> {
+    let attr = attrs.iter().find(|attr| attr.path.is_ident("property"))?;
+    if attr.tokens.is_empty() {
+        return Some(Vec::new());
+    }
+    match attr.parse_meta() {
+        Ok(syn::Meta::List(list)) => Some(list.nested.into_iter().collect()),
+        _ => {
+            log::warn!("could not parse the arguments of #[property(...)]");
+            Some(Vec::new())
+        }
+    }
+}
+
+/// Split the inputs of `sig` between the `self` receiver, and the other
+/// parameters.
+///
+/// The receiver can be `self`, `&self`, `&mut self` or typed (like
+/// `self: Box<Self>`). It is always the first input if present.
+pub(super) fn split_receiver(
+    sig: &syn::Signature,
+) -> (Option<&syn::FnArg>, syn::punctuated::Iter<'_, syn::FnArg>) {
+    let mut inputs = sig.inputs.iter();
+    let receiver = match sig.receiver() {
+        Some(_) => inputs.next(),
+        None => None,
+    };
+    (receiver, inputs)
+}
+
 /// Get this type's base name if it has one.
+///
+/// Containers and references are mapped to the type seen by Godot:
+/// - `Option<T>` becomes an optional `T`
+/// - `&T`, `Ref<T>`, `TRef<T>`, `Instance<T>` and `RefInstance<T>` become `T`
+/// - `Vec<T>` becomes `Array`, and `Result<T, E>` becomes `Dictionary` (gdnative
+///   converts it to `{ "Ok": value }` or `{ "Err": error }`)
+/// - Other generic types keep their base name (`MyType<T>` becomes `MyType`)
 pub(super) fn get_type_name(typ: syn::Type) -> Option<Type> {
     match typ {
         syn::Type::Path(path) => {
@@ -28,23 +217,29 @@ pub(super) fn get_type_name(typ: syn::Type) -> Option<Type> {
                     args,
                     ..
                 }) => {
-                    if type_name == "Option" && args.len() == 1 {
-                        if let Some(syn::GenericArgument::Type(typ)) = args.first().cloned() {
-                            if let Some(Type::Named(name)) = get_type_name(typ) {
-                                Some(Type::Option(name))
-                            } else {
-                                None
-                            }
-                        } else {
-                            None
+                    // skips lifetimes, like in `TRef<'a, Node>`
+                    let first_type = args.iter().find_map(|arg| match arg {
+                        syn::GenericArgument::Type(typ) => Some(typ.clone()),
+                        _ => None,
+                    });
+                    match type_name.as_str() {
+                        "Option" => match first_type.and_then(get_type_name) {
+                            Some(Type::Named(name)) => Some(Type::Option(name)),
+                            _ => None,
+                        },
+                        // references to Godot objects or to other `NativeClass`es
+                        "Ref" | "TRef" | "Instance" | "RefInstance" => {
+                            first_type.and_then(get_type_name)
                         }
-                    } else {
-                        None
+                        "Vec" => Some(Type::Named("Array".to_string())),
+                        "Result" => Some(Type::Named("Dictionary".to_string())),
+                        _ => Some(Type::Named(type_name)),
                     }
                 }
                 syn::PathArguments::Parenthesized(_) => None,
             }
         }
+        syn::Type::Reference(reference) => get_type_name(*reference.elem),
         syn::Type::Tuple(tuple) => {
             if tuple.elems.is_empty() {
                 Some(Type::Unit)
@@ -57,7 +252,15 @@ pub(super) fn get_type_name(typ: syn::Type) -> Option<Type> {
 }
 
 /// Extract '\n'-separated documentation from `attrs`.
-pub(super) fn get_docs(attrs: &[syn::Attribute]) -> String {
+///
+/// `file` is the file in which `attrs` are written: it is used to resolve
+/// `#[doc = include_str!("...")]` if [`DocumentationOptions::resolve_include_str`]
+/// is set.
+pub(super) fn get_docs(
+    attrs: &[syn::Attribute],
+    file: &Path,
+    options: &DocumentationOptions,
+) -> String {
     let mut doc = String::new();
     let mut first_newline = true;
     for attr in attrs {
@@ -65,18 +268,71 @@ pub(super) fn get_docs(attrs: &[syn::Attribute]) -> String {
             continue;
         }
 
-        if let Ok(syn::Meta::NameValue(syn::MetaNameValue {
+        let line = if let Ok(syn::Meta::NameValue(syn::MetaNameValue {
             lit: syn::Lit::Str(lit_str),
             ..
         })) = attr.parse_meta()
         {
-            if first_newline {
-                first_newline = false;
-            } else {
-                doc.push('\n');
+            lit_str.value()
+        } else if let Some(included) = get_included_doc(attr) {
+            if !options.resolve_include_str {
+                log::debug!("ignoring included documentation");
+                continue;
+            }
+            let included = match included {
+                Some(included) => included,
+                None => {
+                    log::warn!(
+                        "could not resolve included documentation in {}: the argument of 'include_str!' is not a string literal",
+                        file.display()
+                    );
+                    continue;
+                }
+            };
+            let path = file
+                .parent()
+                .map(|directory| directory.join(&included))
+                .unwrap_or_else(|| included.into());
+            match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(err) => {
+                    log::warn!(
+                        "could not read included documentation at {}: {}",
+                        path.display(),
+                        err
+                    );
+                    continue;
+                }
             }
-            doc.push_str(&lit_str.value());
+        } else {
+            continue;
+        };
+        if first_newline {
+            first_newline = false;
+        } else {
+            doc.push('\n');
         }
+        doc.push_str(&line);
     }
     doc
 }
+
+/// If `attr` is of the form `#[doc = include_str!(...)]`, returns the included
+/// path if it is a string literal, or `Some(None)` otherwise.
+fn get_included_doc(attr: &syn::Attribute) -> Option<Option<String>> {
+    use syn::parse::{ParseStream, Parser};
+
+    let parser = |input: ParseStream| {
+        input.parse::<syn::Token![=]>()?;
+        input.parse::<syn::Macro>()
+    };
+    let mac = parser.parse2(attr.tokens.clone()).ok()?;
+    if !mac.path.is_ident("include_str") {
+        return None;
+    }
+    Some(
+        mac.parse_body::<syn::LitStr>()
+            .ok()
+            .map(|path| path.value()),
+    )
+}
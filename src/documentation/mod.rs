@@ -2,10 +2,15 @@
 
 mod builder;
 mod helpers;
+#[cfg(test)]
+mod tests;
 
 use crate::Result;
 use helpers::*;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
 
 /// Attribute in a function parameter.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -16,6 +21,55 @@ pub enum ParameterAttribute {
     Opt,
 }
 
+/// RPC mode of an exported method, given by `#[export(rpc = "...")]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RpcMode {
+    /// No RPC (the default)
+    Disabled,
+    /// `rpc = "remote"`
+    Remote,
+    /// `rpc = "remote_sync"`
+    RemoteSync,
+    /// `rpc = "master"`
+    Master,
+    /// `rpc = "master_sync"`
+    MasterSync,
+    /// `rpc = "puppet"`
+    Puppet,
+    /// `rpc = "puppet_sync"`
+    PuppetSync,
+}
+
+impl RpcMode {
+    /// Parse the value of `rpc = "..."` in `#[export(...)]`.
+    pub(crate) fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "disabled" => Some(Self::Disabled),
+            "remote" => Some(Self::Remote),
+            "remote_sync" => Some(Self::RemoteSync),
+            "master" => Some(Self::Master),
+            "master_sync" => Some(Self::MasterSync),
+            "puppet" => Some(Self::Puppet),
+            "puppet_sync" => Some(Self::PuppetSync),
+            _ => None,
+        }
+    }
+
+    /// Keyword used in gdscript to declare a function with this RPC mode, like
+    /// `remotesync` in `remotesync func my_function()`.
+    pub fn gdscript_keyword(self) -> Option<&'static str> {
+        match self {
+            Self::Disabled => None,
+            Self::Remote => Some("remote"),
+            Self::RemoteSync => Some("remotesync"),
+            Self::Master => Some("master"),
+            Self::MasterSync => Some("mastersync"),
+            Self::Puppet => Some("puppet"),
+            Self::PuppetSync => Some("puppetsync"),
+        }
+    }
+}
+
 /// Most type are simply `String`, but not all (e.g. return type)
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Type {
@@ -35,6 +89,9 @@ pub struct Method {
     /// Name of the method.
     pub name: String,
     /// Name of the type that is being `impl`emented.
+    ///
+    /// This is the name seen by Godot, after applying
+    /// [`ConfigFile::rename_classes`](crate::ConfigFile::rename_classes).
     pub self_type: String,
     /// Parameters of the method (excluding `self`).
     ///
@@ -45,6 +102,8 @@ pub struct Method {
     pub parameters: Vec<(String, Type, ParameterAttribute)>,
     /// Return type of the method.
     pub return_type: Type,
+    /// RPC mode of the method.
+    pub rpc_mode: RpcMode,
     /// Documentation associated with the method
     ///
     /// # Note
@@ -52,6 +111,21 @@ pub struct Method {
     pub documentation: String,
     /// File in which the method was declared
     pub file: PathBuf,
+    /// `true` if the `missing_docs` lint is allowed for this method (e.g. with
+    /// `#[allow(missing_docs)]` on the method, its `impl` block or an enclosing
+    /// module).
+    pub allow_missing_docs: bool,
+}
+
+/// Usage of a property in Godot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PropertyUsage {
+    /// Godot's default usage: the property is visible in the editor, stored and
+    /// sent over the network.
+    Default,
+    /// `#[property(no_editor)]`: the property is hidden from the editor, but still
+    /// stored and sent over the network.
+    NoEditor,
 }
 
 /// Property exported to godot
@@ -73,18 +147,56 @@ pub struct Method {
 /// ```text
 /// name: "my_property",
 /// typ: Type::Named("String"),
-/// documentation: "Some doc"
+/// default: None,
+/// documentation: "Some doc",
+/// usage: PropertyUsage::Default,
+/// hint: None,
+/// other_options: [],
+/// allow_missing_docs: false
 /// ```
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Property {
-    /// Name of the property
+    /// Name of the property in Godot.
+    ///
+    /// This is the name of the field, or the path given by
+    /// `#[property(path = "...")]`.
     pub name: String,
     /// Type of the property
     pub typ: Type,
+    /// Default value of the property, given by `#[property(default = ...)]`, as
+    /// written in the source.
+    pub default: Option<String>,
     /// Documentation associated with  the property
     pub documentation: String,
+    /// Usage of the property, given by the options of `#[property(...)]`.
+    pub usage: PropertyUsage,
+    /// Path of the function giving the editor hint of the property, given by
+    /// `#[property(hint = "...")]`.
+    pub hint: Option<String>,
+    /// Names of the other options of `#[property(...)]` (e.g. `before_get`), in
+    /// order.
+    ///
+    /// Options that gdnative does not accept are also logged as warnings.
+    pub other_options: Vec<String>,
+    /// `true` if the `missing_docs` lint is allowed for this property.
+    pub allow_missing_docs: bool,
 }
 
+impl Property {
+    /// Category of the property in the Godot inspector, given by
+    /// `#[property(path = "category/name")]`.
+    ///
+    /// This is the part of [`name`](Self::name) before the last `/`.
+    pub fn category(&self) -> Option<&str> {
+        self.name
+            .rfind('/')
+            .map(|separator| &self.name[..separator])
+    }
+}
+
+/// Options of `#[property(...)]` accepted by gdnative, but not used by this crate.
+const PROPERTY_HOOKS: &[&str] = &["before_get", "after_get", "before_set", "after_set"];
+
 /// Structure that derive `NativeClass`
 ///
 /// # Note
@@ -92,8 +204,14 @@ pub struct Property {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct GdnativeClass {
     /// Name of the structure
+    ///
+    /// This is the name seen by Godot, after applying
+    /// [`ConfigFile::rename_classes`](crate::ConfigFile::rename_classes).
     pub name: String,
     /// Name of the type in `#[inherit(...)]`
+    ///
+    /// gdnative requires this attribute: if it is omitted, a warning is emitted
+    /// and this is `Reference`.
     pub inherit: String,
     /// Documentation associated with the structure.
     pub documentation: String,
@@ -104,9 +222,48 @@ pub struct GdnativeClass {
     /// As per `gdnative`'s documentation, exported methods are
     /// - In a `#[methods]` impl block
     /// - Either `new`, or marked with `#[export]`
+    ///
+    /// Methods of every such impl block are gathered here, in the order in which
+    /// the blocks (and the methods inside them) appear in the sources.
     pub methods: Vec<Method>,
     /// File in which the `struct` was declared
     pub file: PathBuf,
+    /// `true` if the `missing_docs` lint is allowed for this structure.
+    pub allow_missing_docs: bool,
+}
+
+/// Options controlling how the documentation is extracted from the sources.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DocumentationOptions {
+    /// Resolve `#[doc = include_str!("...")]` attributes, by reading the included
+    /// file relative to the file containing the attribute.
+    pub(crate) resolve_include_str: bool,
+    /// If set, only document the classes marked with this attribute, or declared in
+    /// a module marked with it.
+    pub(crate) include_only_attr: Option<String>,
+    /// If set, module files bigger than this (in bytes) are skipped.
+    pub(crate) max_file_bytes: Option<u64>,
+}
+
+/// Exported item without documentation, as reported by
+/// [`Documentation::missing_docs`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MissingDocs {
+    /// Name of the item: `Class`, `Class.method` or `Class.property`.
+    pub item: String,
+    /// File in which the item was declared.
+    pub file: PathBuf,
+}
+
+impl std::fmt::Display for MissingDocs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "missing documentation for '{}' (in {})",
+            self.item,
+            self.file.display()
+        )
+    }
 }
 
 /// Holds the documentation for the crate.
@@ -125,22 +282,66 @@ pub struct Documentation {
 }
 
 impl Documentation {
-    pub(crate) fn from_root_file(name: String, root_file: PathBuf) -> Result<Self> {
+    /// Returns the names of the documented classes.
+    ///
+    /// Those are the names seen by Godot (e.g. after applying
+    /// [`ConfigFile::rename_classes`](crate::ConfigFile::rename_classes)).
+    pub fn class_names(&self) -> HashSet<&str> {
+        self.classes.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the exported classes, methods and properties that have no
+    /// documentation, sorted by class name.
+    ///
+    /// Items for which the `missing_docs` lint is allowed (e.g. with
+    /// `#[allow(missing_docs)]`) are not reported.
+    pub fn missing_docs(&self) -> Vec<MissingDocs> {
+        let mut classes: Vec<_> = self.classes.values().collect();
+        classes.sort_by(|class1, class2| class1.name.cmp(&class2.name));
+        let mut missing_docs = Vec::new();
+        for class in classes {
+            if !class.allow_missing_docs && class.documentation.trim().is_empty() {
+                missing_docs.push(MissingDocs {
+                    item: class.name.clone(),
+                    file: class.file.clone(),
+                })
+            }
+            for property in &class.properties {
+                if !property.allow_missing_docs && property.documentation.trim().is_empty() {
+                    missing_docs.push(MissingDocs {
+                        item: format!("{}.{}", class.name, property.name),
+                        file: class.file.clone(),
+                    })
+                }
+            }
+            for method in &class.methods {
+                if !method.allow_missing_docs && method.documentation.trim().is_empty() {
+                    missing_docs.push(MissingDocs {
+                        item: format!("{}.{}", class.name, method.name),
+                        file: method.file.clone(),
+                    })
+                }
+            }
+        }
+        missing_docs
+    }
+
+    pub(crate) fn from_root_file(
+        name: String,
+        root_file: PathBuf,
+        options: DocumentationOptions,
+    ) -> Result<Self> {
         use syn::visit::Visit;
 
         let root_file_content = read_file_at(&root_file)?;
-        let mut builder = builder::DocumentationBuilder {
-            documentation: Self {
-                name,
-                root_file: root_file.clone(),
-                root_documentation: String::new(),
-                classes: HashMap::new(),
-            },
-            current_file: (root_file, true),
-            current_module: Vec::new(),
-            error: None,
-        };
-        let root_documentation = get_docs(&root_file_content.attrs);
+        let mut builder = builder::DocumentationBuilder::new(name, root_file, options);
+        builder.in_included_module = builder.has_include_only_attr(&root_file_content.attrs);
+        builder.allow_missing_docs = allows_missing_docs(&root_file_content.attrs, false);
+        let root_documentation = get_docs(
+            &root_file_content.attrs,
+            &builder.current_file.0,
+            &builder.options,
+        );
         for item in root_file_content.items {
             builder.visit_item(&item);
             if let Some(error) = builder.error.take() {
@@ -148,13 +349,24 @@ impl Documentation {
             }
         }
         builder.documentation.root_documentation = root_documentation;
+        for class in &builder.excluded_classes {
+            builder.documentation.classes.remove(class);
+        }
+        builder.resolve_type_aliases();
+        builder.check_registered_classes();
         Ok(builder.documentation)
     }
 }
 
 impl GdnativeClass {
     /// Check that the method is exported, parse it, and add it to the class.
-    fn add_method(&mut self, method: &syn::ImplItemMethod, file: PathBuf) {
+    fn add_method(
+        &mut self,
+        method: &syn::ImplItemMethod,
+        file: PathBuf,
+        allow_missing_docs: bool,
+        options: &DocumentationOptions,
+    ) {
         let syn::ImplItemMethod {
             vis, attrs, sig, ..
         } = method;
@@ -164,24 +376,22 @@ impl GdnativeClass {
             return;
         }
         // not exported nor a constructor
-        if !(attributes_contains(attrs, "export") || sig.ident == "new") {
-            return;
-        }
+        let rpc_mode = match get_export_rpc_mode(attrs) {
+            Some(rpc_mode) => rpc_mode,
+            None if sig.ident == "new" => RpcMode::Disabled,
+            None => return,
+        };
 
-        let has_self = sig.receiver().is_some();
+        let (receiver, mut parameters) = split_receiver(sig);
+        let has_self = receiver.is_some();
         let syn::Signature {
             ident: method_name,
-            inputs,
             output,
             ..
         } = sig;
 
-        let mut parameters = inputs.into_iter();
-        if has_self {
-            parameters.next();
-        }
         parameters.next(); // inherit argument
-        let parameters = {
+        let mut parameters = {
             let mut params = Vec::new();
             for arg in parameters {
                 if let syn::FnArg::Typed(syn::PatType { attrs, pat, ty, .. }) = arg {
@@ -208,10 +418,19 @@ impl GdnativeClass {
             params
         };
 
-        let return_type = match output {
+        let mut return_type = match output {
             syn::ReturnType::Default => Type::Unit,
             syn::ReturnType::Type(_, typ) => get_type_name(*typ.clone()).unwrap_or(Type::Unit),
         };
+        // `Self` (or `Instance<Self>`...) is the class itself
+        let replace_self = |typ: &mut Type| match typ {
+            Type::Named(name) | Type::Option(name) if name == "Self" => *name = self.name.clone(),
+            _ => {}
+        };
+        for (_, typ, _) in &mut parameters {
+            replace_self(typ);
+        }
+        replace_self(&mut return_type);
         log::trace!(
             "added method {}: parameters = {:?}, return = {:?}",
             method_name,
@@ -224,24 +443,95 @@ impl GdnativeClass {
             self_type: self.name.clone(),
             parameters,
             return_type,
-            documentation: get_docs(&attrs),
+            rpc_mode,
+            documentation: get_docs(&attrs, &file, options),
             file,
+            allow_missing_docs: allows_missing_docs(attrs, allow_missing_docs),
         })
     }
 
     /// Extract `#[property]` fields
-    fn get_properties(&mut self, fields: &syn::FieldsNamed) {
+    fn get_properties(
+        &mut self,
+        fields: &syn::FieldsNamed,
+        file: &Path,
+        allow_missing_docs: bool,
+        options: &DocumentationOptions,
+    ) {
         for field in &fields.named {
-            if attributes_contains(&field.attrs, "property") {
+            if let Some(arguments) = get_property_arguments(&field.attrs) {
+                let mut name = field
+                    .ident
+                    .as_ref()
+                    .map(|ident| ident.to_string())
+                    .unwrap_or_default();
+                let mut usage = PropertyUsage::Default;
+                let mut default = None;
+                let mut hint = None;
+                let mut other_options = Vec::new();
+                for argument in arguments {
+                    let meta = match argument {
+                        syn::NestedMeta::Meta(meta) => meta,
+                        syn::NestedMeta::Lit(_) => continue,
+                    };
+                    match &meta {
+                        syn::Meta::NameValue(syn::MetaNameValue { path, lit, .. })
+                            if path.is_ident("default") =>
+                        {
+                            default = get_literal(lit);
+                            continue;
+                        }
+                        syn::Meta::NameValue(syn::MetaNameValue {
+                            path,
+                            lit: syn::Lit::Str(lit),
+                            ..
+                        }) if path.is_ident("path") => {
+                            name = lit.value();
+                            continue;
+                        }
+                        syn::Meta::NameValue(syn::MetaNameValue {
+                            path,
+                            lit: syn::Lit::Str(lit),
+                            ..
+                        }) if path.is_ident("hint") => {
+                            hint = Some(lit.value());
+                            continue;
+                        }
+                        syn::Meta::Path(path) if path.is_ident("no_editor") => {
+                            usage = PropertyUsage::NoEditor;
+                            continue;
+                        }
+                        _ => {}
+                    }
+                    let option = meta
+                        .path()
+                        .segments
+                        .iter()
+                        .map(|segment| segment.ident.to_string())
+                        .collect::<Vec<_>>()
+                        .join("::");
+                    if !PROPERTY_HOOKS.contains(&option.as_str()) {
+                        log::warn!(
+                            "unknown option in #[property(...)] of '{}': {}",
+                            name,
+                            option
+                        );
+                    }
+                    other_options.push(option);
+                }
+                if !name.split('/').all(is_identifier) {
+                    log::warn!("'{}' is not a valid Godot property name", name);
+                }
                 let property = Property {
-                    name: field
-                        .ident
-                        .as_ref()
-                        .map(|ident| ident.to_string())
-                        .unwrap_or_default(),
+                    name,
                     // FIXME: log unsupported types
                     typ: get_type_name(field.ty.clone()).unwrap_or(Type::Unit),
-                    documentation: get_docs(&field.attrs),
+                    default,
+                    documentation: get_docs(&field.attrs, file, options),
+                    usage,
+                    hint,
+                    other_options,
+                    allow_missing_docs: allows_missing_docs(&field.attrs, allow_missing_docs),
                 };
                 log::trace!(
                     "added property '{}' of type {:?}",
@@ -0,0 +1,1415 @@
+//! Tests for the extraction of the documentation from Rust sources.
+//!
+//! Each test writes a small crate in a temporary directory, and builds its
+//! [`Documentation`].
+
+use super::*;
+use std::{cell::RefCell, fs};
+
+thread_local! {
+    /// Warnings logged by the current test.
+    static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Logger storing the warnings in [`WARNINGS`], so that they can be checked.
+struct TestLogger;
+
+impl log::Log for TestLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            WARNINGS.with(|warnings| warnings.borrow_mut().push(record.args().to_string()))
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Run `f`, returning the warnings it logged.
+fn warnings_of(f: impl FnOnce()) -> Vec<String> {
+    // fails if the logger is already set, which is fine
+    let _ = log::set_logger(&TestLogger);
+    log::set_max_level(log::LevelFilter::Warn);
+    WARNINGS.with(|warnings| warnings.borrow_mut().clear());
+    f();
+    WARNINGS.with(|warnings| std::mem::take(&mut *warnings.borrow_mut()))
+}
+
+/// Write `files` in a fresh temporary directory named after `test_name`.
+///
+/// Each file is given by its path relative to this directory, and its content.
+///
+/// Returns the path of the directory.
+fn write_crate(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let directory = std::env::temp_dir()
+        .join("gdnative-doc-tests")
+        .join(test_name);
+    if directory.exists() {
+        fs::remove_dir_all(&directory).unwrap();
+    }
+    for (path, content) in files {
+        let path = directory.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    directory
+}
+
+/// Write `files` using [`write_crate`], and build the documentation using the
+/// first file as the root file.
+fn build(test_name: &str, files: &[(&str, &str)]) -> Documentation {
+    build_with_options(test_name, files, DocumentationOptions::default())
+}
+
+/// Same as [`build`], with the given `options`.
+fn build_with_options(
+    test_name: &str,
+    files: &[(&str, &str)],
+    options: DocumentationOptions,
+) -> Documentation {
+    let directory = write_crate(test_name, files);
+    Documentation::from_root_file(test_name.to_string(), directory.join(files[0].0), options)
+        .unwrap()
+}
+
+/// Returns the names of the methods in `class`.
+fn method_names(class: &GdnativeClass) -> Vec<&str> {
+    class
+        .methods
+        .iter()
+        .map(|method| method.name.as_str())
+        .collect()
+}
+
+#[test]
+fn methods_in_multiple_impl_blocks() {
+    let documentation = build(
+        "methods_in_multiple_impl_blocks",
+        &[
+            (
+                "lib.rs",
+                r#"
+mod player_methods;
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+
+#[methods]
+impl Player {
+    pub fn new(_: &Node) -> Self { todo!() }
+    #[export]
+    pub fn jump(&self, _: &Node) {}
+}
+"#,
+            ),
+            (
+                "player_methods.rs",
+                r#"
+#[methods]
+impl Player {
+    #[export]
+    pub fn run(&self, _: &Node) {}
+    #[export]
+    pub fn walk(&self, _: &Node) {}
+}
+"#,
+            ),
+        ],
+    );
+
+    let class = &documentation.classes["Player"];
+    assert_eq!(method_names(class), ["run", "walk", "new", "jump"]);
+    assert!(class.methods[0].file.ends_with("player_methods.rs"));
+    assert!(class.methods[2].file.ends_with("lib.rs"));
+}
+
+#[test]
+fn included_documentation() {
+    let files = &[
+        (
+            "src/lib.rs",
+            r#"
+#![doc = include_str!("../README.md")]
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+#[doc = include_str!("../doc/player.md")]
+pub struct Player {}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+/// Inline documentation
+#[doc = include_str!("missing.md")]
+pub struct Enemy {}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+#[doc = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md"))]
+pub struct Npc {}
+"#,
+        ),
+        ("README.md", "Crate documentation"),
+        ("doc/player.md", "# Player\n\nControlled by the user."),
+    ];
+
+    let warnings = warnings_of(|| {
+        let documentation = build("included_documentation_disabled", files);
+        assert_eq!(documentation.root_documentation, "");
+        assert_eq!(documentation.classes["Player"].documentation, "");
+    });
+    assert!(warnings.is_empty(), "{:?}", warnings);
+
+    let warnings = warnings_of(|| {
+        let documentation = build_with_options(
+            "included_documentation",
+            files,
+            DocumentationOptions {
+                resolve_include_str: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(documentation.root_documentation, "Crate documentation");
+        assert_eq!(
+            documentation.classes["Player"].documentation,
+            "# Player\n\nControlled by the user."
+        );
+        assert_eq!(
+            documentation.classes["Enemy"].documentation,
+            " Inline documentation"
+        );
+        assert_eq!(documentation.classes["Npc"].documentation, "");
+    });
+    let root_file = std::env::temp_dir()
+        .join("gdnative-doc-tests")
+        .join("included_documentation")
+        .join("src")
+        .join("lib.rs");
+    assert_eq!(warnings.len(), 2, "{:?}", warnings);
+    assert!(warnings[0].starts_with("could not read included documentation"));
+    assert_eq!(
+        warnings[1],
+        format!(
+            "could not resolve included documentation in {}: the argument of 'include_str!' is not a string literal",
+            root_file.display()
+        )
+    );
+}
+
+#[test]
+fn include_only_attr() {
+    let files = &[
+        (
+            "lib.rs",
+            r#"
+mod documented;
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Excluded {}
+
+#[methods]
+impl Excluded {
+    pub fn new(_: &Node) -> Self { todo!() }
+}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+#[godot_doc]
+pub struct Included {}
+
+#[godot_doc]
+mod inline {
+    #[derive(NativeClass)]
+    #[inherit(Node)]
+    pub struct InInlineModule {}
+}
+"#,
+        ),
+        (
+            "documented.rs",
+            r#"
+#![godot_doc]
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct InFileModule {}
+"#,
+        ),
+    ];
+
+    let documentation = build("include_only_attr_unset", files);
+    assert_eq!(documentation.classes.len(), 4);
+
+    let documentation = build_with_options(
+        "include_only_attr",
+        files,
+        DocumentationOptions {
+            include_only_attr: Some("godot_doc".to_string()),
+            ..Default::default()
+        },
+    );
+    let mut classes: Vec<_> = documentation.classes.keys().map(String::as_str).collect();
+    classes.sort_unstable();
+    assert_eq!(classes, ["InFileModule", "InInlineModule", "Included"]);
+}
+
+#[test]
+fn module_directory() {
+    let options = DocumentationOptions::default();
+    let mut builder = builder::DocumentationBuilder::new(
+        "test".to_string(),
+        PathBuf::from("src/lib.rs"),
+        options,
+    );
+    assert_eq!(builder.module_directory(), PathBuf::from("src"));
+
+    builder.current_file = (PathBuf::from("src/module/mod.rs"), true);
+    assert_eq!(builder.module_directory(), PathBuf::from("src/module"));
+
+    builder.current_file = (PathBuf::from("src/module.rs"), false);
+    assert_eq!(builder.module_directory(), PathBuf::from("src/module"));
+
+    builder.current_module = vec!["inline".to_string()];
+    assert_eq!(
+        builder.module_directory(),
+        PathBuf::from("src/module/inline")
+    );
+}
+
+#[test]
+fn file_module_in_inline_module() {
+    let documentation = build(
+        "file_module_in_inline_module",
+        &[
+            ("lib.rs", "mod inline { mod nested; }"),
+            (
+                "inline/nested.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Nested {}
+"#,
+            ),
+        ],
+    );
+    assert!(documentation.classes["Nested"]
+        .file
+        .ends_with("inline/nested.rs"));
+}
+
+#[test]
+fn path_attribute_on_inline_module() {
+    let documentation = build(
+        "path_attribute_on_inline_module",
+        &[
+            (
+                "src/lib.rs",
+                r#"
+#[path = "other"]
+mod inline {
+    mod nested;
+    #[path = "renamed.rs"]
+    mod with_path;
+}
+"#,
+            ),
+            (
+                "src/other/nested.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Nested {}
+"#,
+            ),
+            (
+                "src/other/renamed.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct WithPath {}
+"#,
+            ),
+        ],
+    );
+    assert!(documentation.classes["Nested"]
+        .file
+        .ends_with("src/other/nested.rs"));
+    assert!(documentation.classes["WithPath"]
+        .file
+        .ends_with("src/other/renamed.rs"));
+}
+
+#[test]
+fn receiver_is_not_a_parameter() {
+    let documentation = build(
+        "receiver_is_not_a_parameter",
+        &[(
+            "lib.rs",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+
+#[methods]
+impl Player {
+    pub fn new(_owner: &Node) -> Self { todo!() }
+    #[export]
+    pub fn by_ref(&self, _owner: &Node, a: i32) {}
+    #[export]
+    pub fn by_mut(&mut self, _owner: TRef<Node>, a: i32) {}
+    #[export]
+    pub fn by_value(self, _owner: &Node, a: i32) {}
+    #[export]
+    pub fn typed(self: Box<Self>, _owner: &Node, a: i32) {}
+}
+"#,
+        )],
+    );
+
+    let class = &documentation.classes["Player"];
+    assert_eq!(
+        method_names(class),
+        ["new", "by_ref", "by_mut", "by_value", "typed"]
+    );
+    let constructor = &class.methods[0];
+    assert!(!constructor.has_self);
+    assert!(constructor.parameters.is_empty());
+    for method in &class.methods[1..] {
+        assert!(method.has_self);
+        assert_eq!(
+            method.parameters,
+            [(
+                "a".to_string(),
+                Type::Named("i32".to_string()),
+                ParameterAttribute::None
+            )]
+        );
+    }
+}
+
+#[test]
+fn property_usage() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "property_usage",
+            &[(
+                "lib.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    visible: i32,
+    /// Hidden from the editor
+    #[property(no_editor)]
+    hidden: i32,
+    #[property(default = 10, no_editor)]
+    hidden_with_default: i32,
+    #[property(weird, before_get = "Self::before_get", after_set = "Self::after_set")]
+    hooked: i32,
+    not_a_property: i32,
+}
+"#,
+            )],
+        );
+
+        let properties: Vec<_> = documentation.classes["Player"]
+            .properties
+            .iter()
+            .map(|property| (property.name.as_str(), property.usage))
+            .collect();
+        assert_eq!(
+            properties,
+            [
+                ("visible", PropertyUsage::Default),
+                ("hidden", PropertyUsage::NoEditor),
+                ("hidden_with_default", PropertyUsage::NoEditor),
+                ("hooked", PropertyUsage::Default),
+            ]
+        );
+        assert_eq!(
+            documentation.classes["Player"].properties[3].other_options,
+            ["weird", "before_get", "after_set"]
+        );
+    });
+    assert_eq!(
+        warnings,
+        ["unknown option in #[property(...)] of 'hooked': weird"]
+    );
+}
+
+#[test]
+fn property_default() {
+    let documentation = build(
+        "property_default",
+        &[(
+            "lib.rs",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    no_default: i32,
+    #[property(default = 10)]
+    int: i32,
+    #[property(default = 1.5)]
+    float: f32,
+    #[property(no_editor, default = "name")]
+    string: String,
+    #[property(default = true)]
+    boolean: bool,
+}
+"#,
+        )],
+    );
+
+    let defaults: Vec<_> = documentation.classes["Player"]
+        .properties
+        .iter()
+        .map(|property| property.default.as_deref())
+        .collect();
+    assert_eq!(
+        defaults,
+        [
+            None,
+            Some("10"),
+            Some("1.5"),
+            Some("\"name\""),
+            Some("true")
+        ]
+    );
+}
+
+#[test]
+fn property_hint() {
+    let documentation = build(
+        "property_hint",
+        &[(
+            "lib.rs",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    no_hint: i32,
+    #[property(default = 10, hint = "Self::health_hint")]
+    health: i32,
+}
+"#,
+        )],
+    );
+
+    let hints: Vec<_> = documentation.classes["Player"]
+        .properties
+        .iter()
+        .map(|property| property.hint.as_deref())
+        .collect();
+    assert_eq!(hints, [None, Some("Self::health_hint")]);
+}
+
+#[test]
+fn property_path() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "property_path",
+            &[(
+                "lib.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    health: i32,
+    #[property(path = "max_health")]
+    maximum_health: i32,
+    #[property(path = "stats/speed")]
+    speed: f32,
+    #[property(path = "2d speed")]
+    invalid: f32,
+}
+"#,
+            )],
+        );
+        let names: Vec<_> = documentation.classes["Player"]
+            .properties
+            .iter()
+            .map(|property| property.name.as_str())
+            .collect();
+        assert_eq!(names, ["health", "max_health", "stats/speed", "2d speed"]);
+    });
+    assert_eq!(warnings, ["'2d speed' is not a valid Godot property name"]);
+}
+
+#[test]
+fn property_category() {
+    let documentation = build(
+        "property_category",
+        &[(
+            "lib.rs",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property(path = "movement/speed")]
+    speed: f32,
+    #[property(path = "movement/jump/height")]
+    jump_height: f32,
+    #[property]
+    health: i32,
+}
+"#,
+        )],
+    );
+    let categories: Vec<_> = documentation.classes["Player"]
+        .properties
+        .iter()
+        .map(|property| (property.name.as_str(), property.category()))
+        .collect();
+    assert_eq!(
+        categories,
+        [
+            ("movement/speed", Some("movement")),
+            ("movement/jump/height", Some("movement/jump")),
+            ("health", None),
+        ]
+    );
+}
+
+#[test]
+fn root_file_without_extension() {
+    let documentation = build(
+        "root_file_without_extension",
+        &[
+            ("entry", "mod player;"),
+            (
+                "player.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+"#,
+            ),
+        ],
+    );
+    assert!(documentation.classes["Player"].file.ends_with("player.rs"));
+
+    let directory = write_crate("missing_root_file", &[]);
+    let root_file = directory.join("missing.rs");
+    match Documentation::from_root_file(
+        "test".to_string(),
+        root_file.clone(),
+        DocumentationOptions::default(),
+    ) {
+        Err(crate::Error::Io(path, _)) => assert_eq!(path, root_file),
+        result => panic!("expected an IO error, got {:?}", result),
+    }
+}
+
+#[test]
+fn rpc_methods() {
+    let documentation = build(
+        "rpc_methods",
+        &[(
+            "lib.rs",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+
+#[methods]
+impl Player {
+    pub fn new(_: &Node) -> Self { todo!() }
+    #[export]
+    pub fn local(&self, _: &Node) {}
+    #[export(rpc = "remote_sync")]
+    pub fn synced(&self, _: &Node) {}
+    #[export(rpc = "puppet")]
+    pub fn puppet(&self, _: &Node) {}
+}
+"#,
+        )],
+    );
+
+    let methods: Vec<_> = documentation.classes["Player"]
+        .methods
+        .iter()
+        .map(|method| (method.name.as_str(), method.rpc_mode))
+        .collect();
+    assert_eq!(
+        methods,
+        [
+            ("new", RpcMode::Disabled),
+            ("local", RpcMode::Disabled),
+            ("synced", RpcMode::RemoteSync),
+            ("puppet", RpcMode::Puppet),
+        ]
+    );
+    assert_eq!(RpcMode::RemoteSync.gdscript_keyword(), Some("remotesync"));
+}
+
+#[test]
+fn container_types() {
+    let type_name = |typ: &str| get_type_name(syn::parse_str(typ).unwrap());
+    let named = |name: &str| Some(Type::Named(name.to_string()));
+
+    assert_eq!(type_name("i64"), named("i64"));
+    assert_eq!(type_name("Vec<i64>"), named("Array"));
+    assert_eq!(type_name("Vec<Ref<Node>>"), named("Array"));
+    assert_eq!(type_name("Dictionary"), named("Dictionary"));
+    assert_eq!(type_name("Result<i64, String>"), named("Dictionary"));
+    // `HashMap` cannot be converted to a `Variant`
+    assert_eq!(type_name("HashMap<String, i64>"), named("HashMap"));
+    assert_eq!(type_name("Int32Array"), named("Int32Array"));
+    assert_eq!(type_name("&Node"), named("Node"));
+    assert_eq!(type_name("TRef<'a, Node>"), named("Node"));
+    assert_eq!(type_name("gdnative::Ref<Node, Shared>"), named("Node"));
+    assert_eq!(type_name("Instance<Player, Shared>"), named("Player"));
+    assert_eq!(
+        type_name("Option<Ref<Node>>"),
+        Some(Type::Option("Node".to_string()))
+    );
+    assert_eq!(type_name("()"), Some(Type::Unit));
+    assert_eq!(type_name("(i32, i32)"), None);
+}
+
+#[test]
+fn path_attribute() {
+    let documentation = build(
+        "path_attribute",
+        &[
+            (
+                "src/lib.rs",
+                r#"
+#[path = "other/player_file.rs"]
+mod player;
+"#,
+            ),
+            (
+                "src/other/player_file.rs",
+                r#"
+mod enemy;
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+"#,
+            ),
+            (
+                "src/other/enemy.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Enemy {}
+"#,
+            ),
+        ],
+    );
+    assert!(documentation.classes["Player"]
+        .file
+        .ends_with("other/player_file.rs"));
+    assert!(documentation.classes["Enemy"]
+        .file
+        .ends_with("other/enemy.rs"));
+}
+
+#[test]
+fn cyclic_path_attribute() {
+    let files = &[
+        ("lib.rs", "mod a;"),
+        ("a.rs", "#[path = \"b.rs\"]\nmod b;"),
+        ("b.rs", "#[path = \"a.rs\"]\nmod a;"),
+    ];
+    let directory = write_crate("cyclic_path_attribute", files);
+    match Documentation::from_root_file(
+        "test".to_string(),
+        directory.join("lib.rs"),
+        DocumentationOptions::default(),
+    ) {
+        Err(crate::Error::CyclicModule(path)) => assert!(path.ends_with("a.rs")),
+        result => panic!("expected a cyclic module error, got {:?}", result),
+    }
+
+    let directory = write_crate(
+        "self_including_path_attribute",
+        &[("lib.rs", "#[path = \"lib.rs\"]\nmod itself;")],
+    );
+    match Documentation::from_root_file(
+        "test".to_string(),
+        directory.join("lib.rs"),
+        DocumentationOptions::default(),
+    ) {
+        Err(crate::Error::CyclicModule(path)) => assert!(path.ends_with("lib.rs")),
+        result => panic!("expected a cyclic module error, got {:?}", result),
+    }
+}
+
+#[test]
+fn generic_class() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "generic_class",
+            &[(
+                "lib.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Generic<T> {
+    value: T,
+}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct NotGeneric {}
+"#,
+            )],
+        );
+        assert_eq!(documentation.classes.len(), 2);
+    });
+    assert_eq!(
+        warnings,
+        ["GDNative class 'Generic' is generic: generic classes cannot be registered in Godot"]
+    );
+}
+
+#[test]
+fn registered_classes() {
+    let warnings = warnings_of(|| {
+        build(
+            "registered_classes",
+            &[(
+                "lib.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Registered {}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct NotRegistered {}
+
+fn init(handle: InitHandle) {
+    handle.add_class::<Registered>();
+    handle.add_tool_class::<Unknown>();
+}
+
+godot_init!(init);
+"#,
+            )],
+        );
+    });
+    assert_eq!(
+        warnings,
+        [
+            "GDNative class 'NotRegistered' is never registered",
+            "'Unknown' is registered, but no corresponding GDNative class was found",
+        ]
+    );
+}
+
+#[test]
+fn allow_missing_docs() {
+    let documentation = build(
+        "allow_missing_docs",
+        &[
+            (
+                "lib.rs",
+                r#"
+mod allowed;
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Denied {
+    #[property]
+    denied: i32,
+    #[property]
+    #[allow(missing_docs)]
+    allowed: i32,
+}
+
+#[methods]
+impl Denied {
+    pub fn new(_: &Node) -> Self { todo!() }
+    #[export]
+    #[allow(dead_code, missing_docs)]
+    pub fn allowed(&self, _: &Node) {}
+}
+"#,
+            ),
+            (
+                "allowed.rs",
+                r#"
+#![allow(missing_docs)]
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Allowed {}
+
+#[methods]
+#[deny(missing_docs)]
+impl Allowed {
+    pub fn new(_: &Node) -> Self { todo!() }
+}
+"#,
+            ),
+        ],
+    );
+    let denied = &documentation.classes["Denied"];
+    assert!(!denied.allow_missing_docs);
+    let properties: Vec<_> = denied
+        .properties
+        .iter()
+        .map(|property| (property.name.as_str(), property.allow_missing_docs))
+        .collect();
+    assert_eq!(properties, [("denied", false), ("allowed", true)]);
+    let methods: Vec<_> = denied
+        .methods
+        .iter()
+        .map(|method| (method.name.as_str(), method.allow_missing_docs))
+        .collect();
+    assert_eq!(methods, [("new", false), ("allowed", true)]);
+
+    let allowed = &documentation.classes["Allowed"];
+    assert!(allowed.allow_missing_docs);
+    assert!(!allowed.methods[0].allow_missing_docs);
+}
+
+#[test]
+fn missing_docs() {
+    let documentation = build(
+        "missing_docs",
+        &[(
+            "lib.rs",
+            r#"
+/// Documented class
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Documented {
+    /// Documented property
+    #[property]
+    documented: i32,
+    #[property]
+    undocumented_property: i32,
+    #[property]
+    #[allow(missing_docs)]
+    allowed: i32,
+}
+
+#[methods]
+impl Documented {
+    /// Documented constructor
+    pub fn new(_: &Node) -> Self { todo!() }
+    #[export]
+    pub fn undocumented_method(&self, _: &Node) {}
+    #[export]
+    #[allow(missing_docs)]
+    pub fn allowed(&self, _: &Node) {}
+}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Undocumented {}
+"#,
+        )],
+    );
+    let missing_docs: Vec<_> = documentation
+        .missing_docs()
+        .into_iter()
+        .map(|missing_docs| missing_docs.item)
+        .collect();
+    assert_eq!(
+        missing_docs,
+        [
+            "Documented.undocumented_property",
+            "Documented.undocumented_method",
+            "Undocumented"
+        ]
+    );
+}
+
+#[test]
+fn max_file_bytes() {
+    let huge_file = format!(
+        "{}\n#[derive(NativeClass)]\n#[inherit(Node)]\npub struct Huge {{}}\n",
+        "// generated\n".repeat(1000)
+    );
+    let warnings = warnings_of(|| {
+        let documentation = build_with_options(
+            "max_file_bytes",
+            &[
+                ("lib.rs", "mod huge; mod small;"),
+                ("huge.rs", &huge_file),
+                (
+                    "small.rs",
+                    "#[derive(NativeClass)] #[inherit(Node)] pub struct Small {}",
+                ),
+            ],
+            DocumentationOptions {
+                max_file_bytes: Some(1000),
+                ..Default::default()
+            },
+        );
+        let classes: Vec<_> = documentation.classes.keys().collect();
+        assert_eq!(classes, ["Small"]);
+    });
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("huge.rs"));
+}
+
+#[test]
+fn root_file_in_subdirectory() {
+    let documentation = build(
+        "root_file_in_subdirectory",
+        &[
+            ("crates/foo/src/lib.rs", "mod a;"),
+            ("crates/foo/src/a.rs", "mod b;"),
+            (
+                "crates/foo/src/a/b.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct B {}
+"#,
+            ),
+        ],
+    );
+    assert!(documentation.classes["B"]
+        .file
+        .ends_with("crates/foo/src/a/b.rs"));
+}
+
+#[test]
+fn type_aliases() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "type_aliases",
+            &[
+                (
+                    "lib.rs",
+                    r#"
+mod types;
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    health: types::Health,
+    #[property]
+    cycle: Cycle1,
+}
+
+type Cycle1 = Cycle2;
+type Cycle2 = Cycle1;
+
+#[methods]
+impl Player {
+    pub fn new(_: &Node) -> Self { todo!() }
+    #[export]
+    pub fn heal(&mut self, _: &Node, amount: Option<types::Health>) -> Nothing {}
+}
+"#,
+                ),
+                (
+                    "types.rs",
+                    r#"
+pub type Health = HealthInner;
+type HealthInner = i32;
+pub type Nothing = ();
+"#,
+                ),
+            ],
+        );
+        let player = &documentation.classes["Player"];
+        assert_eq!(player.properties[0].typ, Type::Named("i32".to_string()));
+        let heal = &player.methods[1];
+        assert_eq!(heal.parameters[0].1, Type::Option("i32".to_string()));
+        assert_eq!(heal.return_type, Type::Unit);
+    });
+    assert_eq!(warnings, ["cyclic type alias: 'Cycle1'"]);
+}
+
+#[test]
+fn conflicting_type_aliases() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "conflicting_type_aliases",
+            &[(
+                "lib.rs",
+                r#"
+mod a {
+    pub type Id = i32;
+    pub type Same = i32;
+}
+mod b {
+    pub type Id = String;
+    pub type Same = i32;
+}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    id: a::Id,
+    #[property]
+    same: b::Same,
+    #[property]
+    local: Local,
+}
+
+fn helper() {
+    type Local = f32;
+}
+"#,
+            )],
+        );
+        let player = &documentation.classes["Player"];
+        assert_eq!(player.properties[0].typ, Type::Named("Id".to_string()));
+        assert_eq!(player.properties[1].typ, Type::Named("i32".to_string()));
+        assert_eq!(player.properties[2].typ, Type::Named("Local".to_string()));
+    });
+    assert_eq!(
+        warnings,
+        ["type alias 'Id' has conflicting definitions: it will not be resolved"]
+    );
+}
+
+#[test]
+fn identical_cfg_definitions() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "identical_cfg_definitions",
+            &[(
+                "lib.rs",
+                r#"
+/// The player
+#[cfg(unix)]
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    /// Health of the player
+    #[property]
+    health: i32,
+}
+
+/// The player
+#[cfg(windows)]
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    /// Health of the player
+    #[property]
+    health: i32,
+}
+"#,
+            )],
+        );
+        let player = &documentation.classes["Player"];
+        let properties: Vec<_> = player
+            .properties
+            .iter()
+            .map(|property| property.name.as_str())
+            .collect();
+        assert_eq!(properties, ["health"]);
+    });
+    assert!(warnings.is_empty(), "{:?}", warnings);
+}
+
+#[test]
+fn conflicting_cfg_definitions() {
+    let directory = std::env::temp_dir()
+        .join("gdnative-doc-tests")
+        .join("conflicting_cfg_definitions");
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "conflicting_cfg_definitions",
+            &[
+                (
+                    "lib.rs",
+                    r#"
+#[cfg(unix)]
+mod unix;
+#[cfg(windows)]
+mod windows;
+"#,
+                ),
+                (
+                    "unix.rs",
+                    r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    health: i32,
+}
+"#,
+                ),
+                (
+                    "windows.rs",
+                    r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    health: f32,
+}
+"#,
+                ),
+            ],
+        );
+        let player = &documentation.classes["Player"];
+        assert_eq!(player.properties.len(), 1);
+        assert_eq!(player.properties[0].typ, Type::Named("i32".to_string()));
+    });
+    assert_eq!(
+        warnings,
+        [format!(
+            "GDNative class 'Player' has conflicting definitions in '{}' and '{}': keeping the first one",
+            directory.join("unix.rs").display(),
+            directory.join("windows.rs").display()
+        )]
+    );
+}
+
+#[test]
+fn inherit_attribute() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "inherit_attribute",
+            &[(
+                "lib.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(gdnative::api::Node2D)]
+pub struct Player {}
+
+#[derive(NativeClass)]
+pub struct NoInherit {}
+
+#[derive(NativeClass)]
+#[inherit(UnknownBase)]
+pub struct Unknown {}
+"#,
+            )],
+        );
+        assert_eq!(documentation.classes["Player"].inherit, "Node2D");
+        assert_eq!(documentation.classes["NoInherit"].inherit, "Reference");
+        assert_eq!(documentation.classes["Unknown"].inherit, "UnknownBase");
+    });
+    assert_eq!(
+        warnings,
+        ["GDNative class 'NoInherit' has no '#[inherit(...)]' attribute, assuming 'Reference'"]
+    );
+
+    let directory = write_crate(
+        "invalid_inherit_attribute",
+        &[(
+            "lib.rs",
+            r#"
+#[derive(NativeClass)]
+#[inherit()]
+pub struct Player {}
+"#,
+        )],
+    );
+    match Documentation::from_root_file(
+        "test".to_string(),
+        directory.join("lib.rs"),
+        DocumentationOptions::default(),
+    ) {
+        Err(crate::Error::InvalidAttribute(path, message)) => {
+            assert_eq!(path, directory.join("lib.rs"));
+            assert_eq!(
+                message,
+                "expected '#[inherit(Class)]' on 'Player', found '#[inherit()]'"
+            );
+        }
+        result => panic!("expected an invalid attribute error, got {:?}", result),
+    }
+}
+
+#[test]
+fn export_outside_of_methods() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "export_outside_of_methods",
+            &[(
+                "lib.rs",
+                r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {}
+
+#[methods]
+impl Player {
+    pub fn new(_: &Node) -> Self { todo!() }
+}
+
+impl Player {
+    #[export]
+    pub fn not_registered(&self, _: &Node) {}
+    pub fn not_exported(&self) {}
+}
+"#,
+            )],
+        );
+        assert_eq!(method_names(&documentation.classes["Player"]), ["new"]);
+    });
+    assert_eq!(
+        warnings,
+        ["method 'not_registered' is marked with #[export] outside of a #[methods] impl block: it will not be registered"]
+    );
+}
+
+#[test]
+fn invalid_utf8() {
+    let directory = write_crate("invalid_utf8", &[("lib.rs", "mod latin1;")]);
+    let latin1 = directory.join("latin1.rs");
+    fs::write(&latin1, b"/// Caf\xe9\nstruct Player {}\n").unwrap();
+    match Documentation::from_root_file(
+        "test".to_string(),
+        directory.join("lib.rs"),
+        DocumentationOptions::default(),
+    ) {
+        Err(crate::Error::Encoding(path)) => assert_eq!(path, latin1),
+        result => panic!("expected an encoding error, got {:?}", result),
+    }
+}
+
+#[test]
+fn file_included_twice() {
+    let warnings = warnings_of(|| {
+        let documentation = build(
+            "file_included_twice",
+            &[
+                (
+                    "lib.rs",
+                    r#"
+mod player;
+#[path = "player.rs"]
+mod player_again;
+"#,
+                ),
+                (
+                    "player.rs",
+                    r#"
+#[derive(NativeClass)]
+#[inherit(Node)]
+pub struct Player {
+    #[property]
+    health: i32,
+}
+
+#[methods]
+impl Player {
+    pub fn new(_: &Node) -> Self { todo!() }
+}
+"#,
+                ),
+            ],
+        );
+        let player = &documentation.classes["Player"];
+        assert_eq!(player.properties.len(), 1);
+        assert_eq!(method_names(player), ["new"]);
+    });
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("is included by multiple modules"));
+}
+
+#[test]
+fn path_attribute_outside_of_crate() {
+    let documentation = build(
+        "path_attribute_outside_of_crate",
+        &[
+            (
+                "crate/src/lib.rs",
+                r#"
+#[path = "../../shared/shared.rs"]
+mod shared;
+mod player;
+"#,
+            ),
+            (
+                "crate/src/player.rs",
+                r#"
+#[path = "../../shared/enemy.rs"]
+mod enemy;
+"#,
+            ),
+            (
+                "shared/shared.rs",
+                "#[derive(NativeClass)] #[inherit(Node)] pub struct Shared {}",
+            ),
+            (
+                "shared/enemy.rs",
+                "#[derive(NativeClass)] #[inherit(Node)] pub struct Enemy {}",
+            ),
+        ],
+    );
+    assert!(documentation.classes["Shared"]
+        .file
+        .ends_with("path_attribute_outside_of_crate/shared/shared.rs"));
+    assert!(documentation.classes["Enemy"]
+        .file
+        .ends_with("path_attribute_outside_of_crate/shared/enemy.rs"));
+
+    assert_eq!(
+        normalize_path(Path::new("a/./b/../../../c")),
+        Path::new("../c")
+    );
+    assert_eq!(normalize_path(Path::new("/a/../../b")), Path::new("/b"));
+}
+
+#[test]
+fn self_type() {
+    let documentation = build(
+        "self_type",
+        &[(
+            "lib.rs",
+            r#"
+#[derive(NativeClass)]
+#[inherit(Reference)]
+pub struct Builder {}
+
+#[methods]
+impl Builder {
+    pub fn new(_: &Reference) -> Self { todo!() }
+    #[export]
+    pub fn with_child(&self, _: &Reference, child: Instance<Self, Shared>) -> Instance<Self, Unique> { todo!() }
+    #[export]
+    pub fn parent(&self, _: &Reference) -> Option<Instance<Self, Shared>> { todo!() }
+}
+"#,
+        )],
+    );
+    let builder_type = Type::Named("Builder".to_string());
+    let methods = &documentation.classes["Builder"].methods;
+    assert_eq!(methods[0].return_type, builder_type);
+    assert_eq!(methods[1].parameters[0].1, builder_type);
+    assert_eq!(methods[1].return_type, builder_type);
+    assert_eq!(methods[2].return_type, Type::Option("Builder".to_string()));
+}
+
+#[test]
+fn raw_module_name() {
+    let documentation = build(
+        "raw_module_name",
+        &[
+            ("lib.rs", "mod r#type; mod r#match { mod inner; }"),
+            (
+                "type.rs",
+                "#[derive(NativeClass)] #[inherit(Node)] pub struct Type {}",
+            ),
+            (
+                "match/inner.rs",
+                "#[derive(NativeClass)] #[inherit(Node)] pub struct Inner {}",
+            ),
+        ],
+    );
+    assert!(documentation.classes["Type"].file.ends_with("type.rs"));
+    assert!(documentation.classes["Inner"]
+        .file
+        .ends_with("match/inner.rs"));
+}
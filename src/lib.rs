@@ -29,8 +29,8 @@ mod builder;
 mod config;
 pub mod documentation;
 
-pub use builder::{Builder, Package};
-pub use config::ConfigFile;
+pub use builder::{Builder, ClassTransform, GenerationPlan, Package};
+pub use config::{CodeBlockPolicy, ConfigFile};
 #[cfg(feature = "simplelog")]
 pub use simplelog::LevelFilter;
 
@@ -43,9 +43,20 @@ pub enum Error {
     /// IO error (usually caused by non-existent or non-readable files).
     #[error("Error at {0}: {1}")]
     Io(std::path::PathBuf, std::io::Error),
+    /// A source file is not valid UTF-8.
+    #[error("{0} is not valid UTF-8")]
+    Encoding(std::path::PathBuf),
     /// [`syn`] parsing error.
     #[error("{0}")]
     Syn(#[from] syn::Error),
+    /// A module includes itself, or one of its parent modules (via
+    /// `#[path = "..."]`).
+    #[error("Cyclic module inclusion: {0} is already being explored")]
+    CyclicModule(std::path::PathBuf),
+    /// An attribute needed to document the crate is malformed (like
+    /// `#[inherit()]`).
+    #[error("Invalid attribute in {0}: {1}")]
+    InvalidAttribute(std::path::PathBuf, String),
     /// Error while running `cargo metadata`.
     #[error("{0}")]
     Metadata(#[from] cargo_metadata::Error),